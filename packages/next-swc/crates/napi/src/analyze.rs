@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Context as _};
+use napi::bindgen_prelude::*;
+use next_custom_transforms::module_analysis::{analyze_module as analyze, AnalyzeOptions};
+use turbopack_binding::swc::core::{
+    base::{config::IsModule, try_with_handler, Compiler},
+    common::{errors::ColorConfig, FileName, FilePathMapping, SourceMap, GLOBALS},
+    ecma::ast::{EsVersion, Program},
+};
+
+use crate::util::MapErr;
+
+pub struct AnalyzeTask {
+    pub filename: FileName,
+    pub src: String,
+    pub options: Buffer,
+}
+
+#[napi]
+impl Task for AnalyzeTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        GLOBALS.set(&Default::default(), || {
+            let cm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+            let c = Compiler::new(cm.clone());
+
+            let options: AnalyzeOptions = serde_json::from_slice(self.options.as_ref())?;
+            let fm = cm.new_source_file(self.filename.clone(), self.src.clone());
+            let analysis = try_with_handler(
+                cm,
+                turbopack_binding::swc::core::base::HandlerOpts {
+                    color: ColorConfig::Never,
+                    skip_filename: false,
+                },
+                |handler| {
+                    let program = c.parse_js(
+                        fm.clone(),
+                        handler,
+                        EsVersion::latest(),
+                        options.syntax,
+                        // Imports and exports only exist in modules.
+                        IsModule::Bool(true),
+                        None,
+                    )?;
+                    let Program::Module(module) = program else {
+                        bail!("expected a module");
+                    };
+                    Ok(analyze(&fm, module, &options))
+                },
+            )
+            .convert_err()?;
+
+            let analysis_json = serde_json::to_string(&analysis)
+                .context("failed to serialize ModuleAnalysis")
+                .convert_err()?;
+
+            Ok(analysis_json)
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, result: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(result)
+    }
+}
+
+/// Reports the Next.js specific facts of a module, like its directives and
+/// Server Actions, as JSON. Doesn't transform the module.
+#[napi]
+pub fn analyze_module(
+    src: String,
+    options: Buffer,
+    filename: Option<String>,
+    signal: Option<AbortSignal>,
+) -> AsyncTask<AnalyzeTask> {
+    AsyncTask::with_optional_signal(
+        AnalyzeTask {
+            filename: to_filename(filename),
+            src,
+            options,
+        },
+        signal,
+    )
+}
+
+#[napi]
+pub fn analyze_module_sync(
+    src: String,
+    options: Buffer,
+    filename: Option<String>,
+) -> napi::Result<String> {
+    AnalyzeTask {
+        filename: to_filename(filename),
+        src,
+        options,
+    }
+    .compute()
+}
+
+fn to_filename(filename: Option<String>) -> FileName {
+    if let Some(value) = filename {
+        FileName::Real(value.into())
+    } else {
+        FileName::Anon
+    }
+}
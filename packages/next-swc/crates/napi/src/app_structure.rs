@@ -96,6 +96,8 @@ struct ComponentsForJs {
     default: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     route: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "global-error")]
+    global_error: Option<String>,
     metadata: MetadataForJs,
 }
 
@@ -157,6 +159,7 @@ async fn prepare_components_for_js(
         not_found,
         default,
         route,
+        global_error,
         metadata,
     } = &*components.await?;
     let mut result = ComponentsForJs::default();
@@ -178,6 +181,7 @@ async fn prepare_components_for_js(
     add(&mut result.not_found, project_path, not_found).await?;
     add(&mut result.default, project_path, default).await?;
     add(&mut result.route, project_path, route).await?;
+    add(&mut result.global_error, project_path, global_error).await?;
 
     let meta = &mut result.metadata;
     add_meta_vec(&mut meta.icon, project_path, metadata.icon.iter()).await?;
@@ -0,0 +1,207 @@
+//! Structured diagnostics for the `transform` APIs, for tooling that can't
+//! parse the rendered terminal output.
+
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use turbopack_binding::swc::core::common::{
+    errors::{DiagnosticBuilder, DiagnosticId, Emitter, Handler, Level, HANDLER},
+    BytePos, SourceMap, Span,
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonDiagnostic {
+    pub severity: &'static str,
+    pub message: String,
+    /// The code of the diagnostic, or of the transform which emitted it.
+    pub code: Option<String>,
+    pub file: Option<String>,
+    pub span: Option<JsonSpan>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonSpan {
+    /// Byte offset into the file.
+    pub start: u32,
+    /// Exclusive byte offset into the file.
+    pub end: u32,
+    pub start_position: JsonPosition,
+    pub end_position: JsonPosition,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonPosition {
+    /// 1-based.
+    pub line: usize,
+    /// 0-based, in characters.
+    pub column: usize,
+}
+
+struct JsonEmitter {
+    cm: Arc<SourceMap>,
+    diagnostics: Arc<Mutex<Vec<JsonDiagnostic>>>,
+}
+
+impl Emitter for JsonEmitter {
+    fn emit(&mut self, db: &DiagnosticBuilder<'_>) {
+        let severity = match db.level {
+            Level::Warning => "warning",
+            Level::Note | Level::Help => "info",
+            _ => "error",
+        };
+        let code = match &db.code {
+            Some(DiagnosticId::Error(code) | DiagnosticId::Lint(code)) => Some(code.clone()),
+            None => next_custom_transforms::diagnostics::current_error_code().map(String::from),
+        };
+        let primary_span = db.span.primary_span().filter(|span| !span.is_dummy());
+
+        let diagnostic = JsonDiagnostic {
+            severity,
+            message: db.message(),
+            code,
+            file: primary_span.map(|span| self.cm.span_to_filename(span).to_string()),
+            span: primary_span.map(|span| self.json_span(span)),
+        };
+        self.diagnostics
+            .lock()
+            .expect("diagnostics lock is poisoned")
+            .push(diagnostic);
+    }
+}
+
+impl JsonEmitter {
+    fn json_span(&self, span: Span) -> JsonSpan {
+        let start = self.cm.lookup_char_pos(span.lo);
+        let end = self.cm.lookup_char_pos(span.hi);
+        let offset = |pos: BytePos| pos.0 - start.file.start_pos.0;
+        JsonSpan {
+            start: offset(span.lo),
+            end: offset(span.hi),
+            start_position: JsonPosition {
+                line: start.line,
+                column: start.col.0,
+            },
+            end_position: JsonPosition {
+                line: end.line,
+                column: end.col.0,
+            },
+        }
+    }
+}
+
+/// Like `try_with_handler`, but collects the diagnostics instead of rendering
+/// them. `op` only returns a value if no errors were emitted.
+pub fn try_with_json_handler<T>(
+    cm: Arc<SourceMap>,
+    op: impl FnOnce(&Handler) -> anyhow::Result<T>,
+) -> (Option<T>, Vec<JsonDiagnostic>) {
+    let diagnostics = Arc::new(Mutex::new(vec![]));
+    let handler = Handler::with_emitter(
+        true,
+        false,
+        Box::new(JsonEmitter {
+            cm,
+            diagnostics: diagnostics.clone(),
+        }),
+    );
+
+    let result = HANDLER.set(&handler, || op(&handler));
+    let output = match result {
+        Ok(output) if !handler.has_errors() => Some(output),
+        // Errors which weren't reported through the handler, like a missing
+        // input file.
+        Err(err) if !handler.has_errors() => {
+            handler.struct_err(&format!("{:?}", err)).emit();
+            None
+        }
+        _ => None,
+    };
+
+    let diagnostics =
+        std::mem::take(&mut *diagnostics.lock().expect("diagnostics lock is poisoned"));
+    (output, diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use next_custom_transforms::{
+        diagnostics::{self, with_error_code},
+        transforms::server_actions::{self, server_actions},
+    };
+    use turbopack_binding::swc::core::{
+        common::{
+            chain, comments::NoopComments, FileName, FilePathMapping, Mark, SourceMap, GLOBALS,
+        },
+        ecma::{
+            ast::{EsVersion, Program},
+            parser::parse_file_as_module,
+            transforms::base::resolver,
+            visit::FoldWith,
+        },
+    };
+
+    use super::try_with_json_handler;
+
+    /// `update` is a Server Action, but isn't async.
+    const SOURCE: &str = r#"export default function Page() {
+  function update() {
+    'use server'
+  }
+  return null
+}
+"#;
+
+    #[test]
+    fn reports_transform_errors() {
+        let cm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+        let (output, diagnostics) = GLOBALS.set(&Default::default(), || {
+            try_with_json_handler(cm.clone(), |_| {
+                let fm =
+                    cm.new_source_file(FileName::Real("/app/page.js".into()), SOURCE.to_string());
+                let module = parse_file_as_module(
+                    &fm,
+                    Default::default(),
+                    EsVersion::latest(),
+                    None,
+                    &mut vec![],
+                )
+                .unwrap();
+                Ok(Program::Module(module).fold_with(&mut chain!(
+                    resolver(Mark::new(), Mark::new(), false),
+                    with_error_code(
+                        diagnostics::SERVER_ACTIONS,
+                        server_actions(
+                            &fm.name,
+                            server_actions::Config {
+                                is_react_server_layer: true,
+                                enabled: true,
+                            },
+                            NoopComments,
+                        ),
+                    ),
+                )))
+            })
+        });
+
+        assert!(output.is_none());
+        assert_eq!(
+            serde_json::to_value(&diagnostics).unwrap(),
+            serde_json::json!([{
+                "severity": "error",
+                "message": "Server actions must be async functions",
+                "code": "next-server-actions",
+                "file": "/app/page.js",
+                "span": {
+                    "start": 44,
+                    "end": 50,
+                    "startPosition": { "line": 2, "column": 11 },
+                    "endPosition": { "line": 2, "column": 17 },
+                },
+            }])
+        );
+    }
+}
@@ -47,8 +47,10 @@ use turbopack_binding::swc::core::{
     common::{sync::Lazy, FilePathMapping, SourceMap},
 };
 
+pub mod analyze;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod app_structure;
+mod diagnostics;
 pub mod mdx;
 pub mod minify;
 #[cfg(not(target_arch = "wasm32"))]
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
 
 use anyhow::{anyhow, bail, Context, Result};
 use napi::{
@@ -33,7 +33,7 @@ use turbopack_binding::{
             diagnostics::PlainDiagnostic,
             error::PrettyPrintError,
             issue::PlainIssue,
-            source_map::Token,
+            source_map::{SourceMap, Token},
             version::{PartialUpdate, TotalUpdate, Update, VersionState},
         },
         ecmascript_hmr_protocol::{ClientUpdateInstruction, ResourceIdentifier},
@@ -78,6 +78,10 @@ pub struct NapiProjectOptions {
     /// Whether to watch he filesystem for file changes.
     pub watch: bool,
 
+    /// Whether the project is built for `next dev` or for `next build`.
+    /// Defaults to `true`.
+    pub dev: Option<bool>,
+
     /// The contents of next.config.js, serialized to JSON.
     pub next_config: String,
 
@@ -143,6 +147,7 @@ impl From<NapiProjectOptions> for ProjectOptions {
             root_path: val.root_path,
             project_path: val.project_path,
             watch: val.watch,
+            dev: val.dev.unwrap_or(true),
             next_config: val.next_config,
             js_config: val.js_config,
             env: val
@@ -328,6 +333,7 @@ impl NapiRoute {
             Route::Page {
                 html_endpoint,
                 data_endpoint,
+                ..
             } => NapiRoute {
                 pathname,
                 r#type: "page",
@@ -344,6 +350,7 @@ impl NapiRoute {
             Route::AppPage {
                 html_endpoint,
                 rsc_endpoint,
+                ..
             } => NapiRoute {
                 pathname,
                 r#type: "app-page",
@@ -884,114 +891,346 @@ pub struct StackFrame {
     pub method_name: Option<String>,
 }
 
-#[napi]
-pub async fn project_trace_source(
-    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
-    frame: StackFrame,
-) -> napi::Result<Option<StackFrame>> {
-    let turbo_tasks = project.turbo_tasks.clone();
-    let traced_frame = turbo_tasks
-        .run_once(async move {
-            let (file, module) = match Url::parse(&frame.file) {
-                Ok(url) => match url.scheme() {
-                    "file" => {
-                        let path = urlencoding::decode(url.path())?.to_string();
-                        let module = url.query_pairs().find(|(k, _)| k == "id");
-                        (path, module.map(|(_, m)| m.into_owned()))
+/// Where a chunk referenced by a stack frame lives, tagged by the output it
+/// was emitted to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum ChunkLocation {
+    /// A chunk emitted for Node.js or the edge runtime, relative to the node
+    /// root.
+    Server(String),
+    /// A chunk served to the browser, relative to the client's `/_next` path.
+    Client(String),
+}
+
+/// A position in an original source, 1-indexed like stack frames.
+#[derive(Debug)]
+struct OriginalPosition {
+    file: String,
+    line: Option<u32>,
+    column: Option<u32>,
+    name: Option<String>,
+}
+
+/// The source maps of the chunks a [StackFrameTracer] traces frames through.
+trait ChunkSourceMaps {
+    type Map: Clone;
+
+    /// Returns the source map of a chunk, or of a single module within it.
+    async fn source_map(
+        &self,
+        location: &ChunkLocation,
+        module: Option<String>,
+    ) -> Result<Option<Self::Map>>;
+
+    /// Returns the original position of a 0-indexed position in the chunk,
+    /// or `None` when the source map can't even guess the original file.
+    async fn lookup(
+        &self,
+        map: &Self::Map,
+        line: usize,
+        column: usize,
+    ) -> Result<Option<OriginalPosition>>;
+}
+
+/// The source maps of a project's output.
+struct ProjectSourceMaps {
+    container: Vc<ProjectContainer>,
+}
+
+impl ChunkSourceMaps for ProjectSourceMaps {
+    type Map = Vc<SourceMap>;
+
+    async fn source_map(
+        &self,
+        location: &ChunkLocation,
+        module: Option<String>,
+    ) -> Result<Option<Vc<SourceMap>>> {
+        let project = self.container.project();
+        let path = match location {
+            ChunkLocation::Server(path) => project.node_root().join(path.clone()),
+            ChunkLocation::Client(path) => project.client_relative_path().join(path.clone()),
+        };
+        Ok(*self.container.get_source_map(path, module).await?)
+    }
+
+    async fn lookup(
+        &self,
+        map: &Vc<SourceMap>,
+        line: usize,
+        column: usize,
+    ) -> Result<Option<OriginalPosition>> {
+        let token = map.lookup_token(line, column).await?;
+        Ok(match &*token {
+            Token::Original(token) => Some(OriginalPosition {
+                file: token.original_file.clone(),
+                // JS stack frames are 1-indexed, source map tokens are 0-indexed
+                line: Some(token.original_line as u32 + 1),
+                column: Some(token.original_column as u32 + 1),
+                name: token.name.clone(),
+            }),
+            Token::Synthetic(token) => {
+                token
+                    .guessed_original_file
+                    .clone()
+                    .map(|file| OriginalPosition {
+                        file,
+                        line: None,
+                        column: None,
+                        name: None,
+                    })
+            }
+        })
+    }
+}
+
+/// Maps stack frames pointing into emitted chunks back to their original
+/// sources. Source maps are fetched once per chunk and module, so a whole
+/// stack trace can be traced within a single `run_once`.
+struct StackFrameTracer<S: ChunkSourceMaps> {
+    source_maps: S,
+    /// The absolute path of the dist dir, with a trailing slash.
+    dist_dir_prefix: String,
+    /// The URL path client chunks are served from, with a trailing slash.
+    client_prefix: String,
+    /// The source maps found so far, by chunk and module.
+    cache: HashMap<(ChunkLocation, Option<String>), S::Map>,
+}
+
+impl StackFrameTracer<ProjectSourceMaps> {
+    async fn for_project(container: Vc<ProjectContainer>) -> Result<Self> {
+        let project = container.project();
+        let dist_dir_prefix = format!(
+            "{}/{}/",
+            project.await?.project_path,
+            project.dist_dir().await?
+        );
+        let client_prefix = format!(
+            "/{}/",
+            project
+                .client_relative_path()
+                .await?
+                .path
+                .trim_start_matches('/')
+        );
+        Ok(Self::new(
+            ProjectSourceMaps { container },
+            dist_dir_prefix,
+            client_prefix,
+        ))
+    }
+}
+
+impl<S: ChunkSourceMaps> StackFrameTracer<S> {
+    fn new(source_maps: S, dist_dir_prefix: String, client_prefix: String) -> Self {
+        Self {
+            source_maps,
+            dist_dir_prefix,
+            client_prefix,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Returns the chunks a stack frame's file may point to, in the order
+    /// they should be tried, along with the module id of the section within
+    /// it. Files within the dist dir are looked up in the output `is_server`
+    /// selects first, and in the other one if that fails. Returns `None` for
+    /// files outside of the project's output.
+    fn locate(
+        &self,
+        file: &str,
+        is_server: bool,
+    ) -> Result<Option<(Vec<ChunkLocation>, Option<String>)>> {
+        let (path, module) = match Url::parse(file) {
+            Ok(url) => {
+                let module = url
+                    .query_pairs()
+                    .find(|(k, _)| k == "id")
+                    .map(|(_, m)| m.into_owned());
+                match url.scheme() {
+                    "file" => (urlencoding::decode(url.path())?.into_owned(), module),
+                    "http" | "https" => {
+                        let path = urlencoding::decode(url.path())?;
+                        return Ok(path.strip_prefix(&self.client_prefix).map(|chunk| {
+                            (vec![ChunkLocation::Client(chunk.to_string())], module)
+                        }));
                     }
                     _ => bail!("Unknown url scheme"),
-                },
-                Err(_) => (frame.file.to_string(), None),
-            };
+                }
+            }
+            Err(_) => (file.to_string(), None),
+        };
 
-            let Some(chunk_base) = file.strip_prefix(
-                &(format!(
-                    "{}/{}/",
-                    project.container.project().await?.project_path,
-                    project.container.project().dist_dir().await?
-                )),
-            ) else {
-                // File doesn't exist within the dist dir
-                return Ok(None);
-            };
+        let Some(chunk_base) = path.strip_prefix(&self.dist_dir_prefix) else {
+            // File doesn't exist within the dist dir
+            return Ok(None);
+        };
 
-            let server_path = project
-                .container
-                .project()
-                .node_root()
-                .join(chunk_base.to_owned());
+        let server = ChunkLocation::Server(chunk_base.to_string());
+        let client = ChunkLocation::Client(chunk_base.to_string());
+        let locations = if is_server {
+            vec![server, client]
+        } else {
+            vec![client, server]
+        };
+        Ok(Some((locations, module)))
+    }
 
-            let client_path = project
-                .container
-                .project()
-                .client_relative_path()
-                .join(chunk_base.to_owned());
+    /// Returns the source map of the first of `locations` that has one.
+    async fn source_map(
+        &mut self,
+        locations: Vec<ChunkLocation>,
+        module: Option<String>,
+    ) -> Result<Option<S::Map>> {
+        let mut result = Ok(None);
+        for location in locations {
+            let key = (location, module.clone());
+            if let Some(map) = self.cache.get(&key) {
+                return Ok(Some(map.clone()));
+            }
 
-            let mut map_result = project
-                .container
-                .get_source_map(server_path, module.clone())
-                .await;
-            if map_result.is_err() {
-                // If the chunk doesn't exist as a server chunk, try a client chunk.
-                // TODO: Properly tag all server chunks and use the `isServer` query param.
-                // Currently, this is inaccurate as it does not cover RSC server
-                // chunks.
-                map_result = project.container.get_source_map(client_path, module).await;
+            result = self.source_maps.source_map(&key.0, key.1.clone()).await;
+            if let Ok(Some(map)) = &result {
+                self.cache.insert(key, map.clone());
+                return result;
             }
-            let map = map_result?.context("chunk/module is missing a sourcemap")?;
+        }
+        result
+    }
 
-            let Some(line) = frame.line else {
-                return Ok(None);
-            };
+    async fn trace(&mut self, frame: &StackFrame) -> Result<Option<StackFrame>> {
+        let Some((locations, module)) = self.locate(&frame.file, frame.is_server)? else {
+            return Ok(None);
+        };
 
-            let token = map
-                .lookup_token(
-                    (line as usize).saturating_sub(1),
-                    (frame.column.unwrap_or(1) as usize).saturating_sub(1),
-                )
-                .await?;
+        let map = self
+            .source_map(locations, module)
+            .await?
+            .context("chunk/module is missing a sourcemap")?;
 
-            let (original_file, line, column, name) = match &*token {
-                Token::Original(token) => (
-                    &token.original_file,
-                    // JS stack frames are 1-indexed, source map tokens are 0-indexed
-                    Some(token.original_line as u32 + 1),
-                    Some(token.original_column as u32 + 1),
-                    token.name.clone(),
-                ),
-                Token::Synthetic(token) => {
-                    let Some(file) = &token.guessed_original_file else {
-                        return Ok(None);
-                    };
-                    (file, None, None, None)
-                }
+        let Some(line) = frame.line else {
+            return Ok(None);
+        };
+
+        let Some(position) = self
+            .source_maps
+            .lookup(
+                &map,
+                (line as usize).saturating_sub(1),
+                (frame.column.unwrap_or(1) as usize).saturating_sub(1),
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let Some(source_file) = position.file.strip_prefix("/turbopack/") else {
+            bail!("Original file ({}) outside project", position.file)
+        };
+
+        let (source_file, is_internal) =
+            if let Some(source_file) = source_file.strip_prefix("[project]/") {
+                (source_file, false)
+            } else {
+                (source_file, true)
             };
 
-            let Some(source_file) = original_file.strip_prefix("/turbopack/") else {
-                bail!("Original file ({}) outside project", original_file)
+        Ok(Some(StackFrame {
+            file: source_file.to_string(),
+            method_name: position.name,
+            line: position.line,
+            column: position.column,
+            is_server: frame.is_server,
+            is_internal: Some(is_internal),
+        }))
+    }
+
+    /// Traces every frame of a stack trace. Frames which can't be traced are
+    /// logged and resolve to `None` instead of failing the entire stack.
+    async fn trace_all(&mut self, frames: &[StackFrame]) -> Vec<Option<StackFrame>> {
+        let mut traced_frames = Vec::with_capacity(frames.len());
+        for frame in frames {
+            let traced_frame = match self.trace(frame).await {
+                Ok(traced_frame) => traced_frame,
+                Err(err) => {
+                    eprintln!(
+                        "Failed to trace stack frame in {}: {}",
+                        frame.file,
+                        PrettyPrintError(&err)
+                    );
+                    None
+                }
             };
+            traced_frames.push(traced_frame);
+        }
+        traced_frames
+    }
+}
 
-            let (source_file, is_internal) =
-                if let Some(source_file) = source_file.strip_prefix("[project]/") {
-                    (source_file, false)
-                } else {
-                    (source_file, true)
-                };
-
-            Ok(Some(StackFrame {
-                file: source_file.to_string(),
-                method_name: name,
-                line,
-                column,
-                is_server: frame.is_server,
-                is_internal: Some(is_internal),
-            }))
+#[napi]
+pub async fn project_trace_source(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    frame: StackFrame,
+) -> napi::Result<Option<StackFrame>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    let traced_frame = turbo_tasks
+        .run_once(async move {
+            StackFrameTracer::for_project(container)
+                .await?
+                .trace(&frame)
+                .await
         })
         .await
         .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
     Ok(traced_frame)
 }
 
+/// Traces a whole stack trace at once. Frames which can't be traced resolve
+/// to `null` instead of failing the entire stack.
+#[napi]
+pub async fn project_trace_source_batch(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    frames: Vec<StackFrame>,
+) -> napi::Result<Vec<Option<StackFrame>>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    let traced_frames = turbo_tasks
+        .run_once(async move {
+            let mut tracer = StackFrameTracer::for_project(container).await?;
+            Ok(tracer.trace_all(&frames).await)
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(traced_frames)
+}
+
+/// Returns the source map of an emitted chunk, or of a single module within
+/// it when the file carries an `id` query param.
+#[napi]
+pub async fn project_get_source_map(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    file_path: String,
+) -> napi::Result<Option<String>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    let source_map = turbo_tasks
+        .run_once(async move {
+            let mut tracer = StackFrameTracer::for_project(container).await?;
+            // The file doesn't tell which output it belongs to, so server chunks are
+            // tried first.
+            let Some((locations, module)) = tracer.locate(&file_path, true)? else {
+                return Ok(None);
+            };
+            let Some(map) = tracer.source_map(locations, module).await? else {
+                return Ok(None);
+            };
+            Ok(Some(map.to_rope().await?.to_str()?.to_string()))
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(source_map)
+}
+
 #[napi]
 pub async fn project_get_source_for_asset(
     #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
@@ -1021,3 +1260,169 @@ pub async fn project_get_source_for_asset(
 
     Ok(source)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    /// Serves a source map, named after its chunk, for each of `chunks`, and
+    /// records every lookup.
+    struct FakeSourceMaps {
+        chunks: Vec<ChunkLocation>,
+        fetched: RefCell<Vec<ChunkLocation>>,
+    }
+
+    impl ChunkSourceMaps for FakeSourceMaps {
+        type Map = String;
+
+        async fn source_map(
+            &self,
+            location: &ChunkLocation,
+            _module: Option<String>,
+        ) -> Result<Option<String>> {
+            self.fetched.borrow_mut().push(location.clone());
+            if !self.chunks.contains(location) {
+                bail!("{:?} doesn't exist", location);
+            }
+            Ok(Some(format!("{:?}", location)))
+        }
+
+        async fn lookup(
+            &self,
+            map: &String,
+            line: usize,
+            column: usize,
+        ) -> Result<Option<OriginalPosition>> {
+            Ok(Some(OriginalPosition {
+                file: format!("/turbopack/[project]/{}", map),
+                line: Some(line as u32 + 1),
+                column: Some(column as u32 + 1),
+                name: None,
+            }))
+        }
+    }
+
+    fn tracer(chunks: Vec<ChunkLocation>) -> StackFrameTracer<FakeSourceMaps> {
+        StackFrameTracer::new(
+            FakeSourceMaps {
+                chunks,
+                fetched: RefCell::new(vec![]),
+            },
+            "/project/.next/".to_string(),
+            "/_next/".to_string(),
+        )
+    }
+
+    fn frame(file: &str, is_server: bool) -> StackFrame {
+        StackFrame {
+            is_server,
+            is_internal: None,
+            file: file.to_string(),
+            line: Some(2),
+            column: Some(3),
+            method_name: None,
+        }
+    }
+
+    fn server(path: &str) -> ChunkLocation {
+        ChunkLocation::Server(path.to_string())
+    }
+
+    fn client(path: &str) -> ChunkLocation {
+        ChunkLocation::Client(path.to_string())
+    }
+
+    #[test]
+    fn locates_chunks_by_the_frame_output() {
+        let tracer = tracer(vec![]);
+        assert_eq!(
+            tracer
+                .locate("file:///project/.next/server/app/page.js?id=42", true)
+                .unwrap(),
+            Some((
+                vec![server("server/app/page.js"), client("server/app/page.js")],
+                Some("42".to_string())
+            ))
+        );
+        assert_eq!(
+            tracer
+                .locate("/project/.next/static/chunks/main.js", false)
+                .unwrap(),
+            Some((
+                vec![
+                    client("static/chunks/main.js"),
+                    server("static/chunks/main.js")
+                ],
+                None
+            ))
+        );
+        assert_eq!(
+            tracer
+                .locate("http://localhost:3000/_next/static/chunks/main.js", true)
+                .unwrap(),
+            Some((vec![client("static/chunks/main.js")], None))
+        );
+        assert_eq!(tracer.locate("/elsewhere/main.js", true).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_other_output() {
+        let mut tracer = tracer(vec![client("server/app/page.js")]);
+        let traced = tracer
+            .trace(&frame("/project/.next/server/app/page.js", true))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(traced.file, r#"Client("server/app/page.js")"#);
+        assert_eq!((traced.line, traced.column), (Some(2), Some(3)));
+        assert_eq!(traced.is_internal, Some(false));
+        assert!(traced.is_server);
+        assert_eq!(
+            *tracer.source_maps.fetched.borrow(),
+            vec![server("server/app/page.js"), client("server/app/page.js")]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetches_each_chunk_once() {
+        let mut tracer = tracer(vec![
+            server("server/app/page.js"),
+            client("static/chunks/main.js"),
+        ]);
+        let frames = [
+            frame("/project/.next/server/app/page.js", true),
+            frame("/project/.next/static/chunks/main.js", false),
+            frame("/project/.next/server/app/page.js", true),
+            frame("/project/.next/static/chunks/main.js", false),
+        ];
+        let traced = tracer.trace_all(&frames).await;
+        assert!(traced.iter().all(Option::is_some));
+        assert_eq!(
+            *tracer.source_maps.fetched.borrow(),
+            vec![
+                server("server/app/page.js"),
+                client("static/chunks/main.js")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn traces_the_rest_of_a_batch_when_a_frame_fails() {
+        let mut tracer = tracer(vec![server("server/app/page.js")]);
+        let frames = [
+            frame("/project/.next/server/missing.js", true),
+            frame("/elsewhere/main.js", true),
+            frame("/project/.next/server/app/page.js", true),
+        ];
+        let traced = tracer.trace_all(&frames).await;
+        assert_eq!(traced.len(), 3);
+        assert!(traced[0].is_none());
+        assert!(traced[1].is_none());
+        assert_eq!(
+            traced[2].as_ref().map(|frame| frame.file.as_str()),
+            Some(r#"Server("server/app/page.js")"#)
+        );
+    }
+}
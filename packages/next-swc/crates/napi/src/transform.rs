@@ -40,11 +40,20 @@ use napi::bindgen_prelude::*;
 use next_custom_transforms::chain_transforms::{custom_before_pass, TransformOptions};
 use turbopack_binding::swc::core::{
     base::{try_with_handler, Compiler, TransformOutput},
-    common::{comments::SingleThreadedComments, errors::ColorConfig, FileName, Mark, GLOBALS},
+    common::{
+        comments::SingleThreadedComments,
+        errors::{ColorConfig, Handler},
+        FileName, Mark, GLOBALS,
+    },
     ecma::transforms::base::pass::noop,
 };
 
-use crate::{complete_output, get_compiler, util::MapErr};
+use crate::{
+    complete_output,
+    diagnostics::{try_with_json_handler, JsonDiagnostic},
+    get_compiler,
+    util::MapErr,
+};
 
 /// Input to transform
 #[derive(Debug)]
@@ -66,76 +75,104 @@ fn skip_filename() -> bool {
     cfg!(debug_assertions)
 }
 
+pub struct TransformTaskOutput {
+    /// Missing if errors were collected as `diagnostics`.
+    output: Option<TransformOutput>,
+    eliminated_packages: FxHashSet<String>,
+    /// Only collected with `jsonDiagnostics`.
+    diagnostics: Option<Vec<JsonDiagnostic>>,
+}
+
+impl TransformTask {
+    fn run_transform(
+        &self,
+        handler: &Handler,
+        options: TransformOptions,
+        eliminated_packages: Rc<RefCell<FxHashSet<String>>>,
+    ) -> anyhow::Result<TransformOutput> {
+        self.c.run(|| {
+            let fm = match &self.input {
+                Input::Source { src } => {
+                    let filename = if options.swc.filename.is_empty() {
+                        FileName::Anon
+                    } else {
+                        FileName::Real(options.swc.filename.clone().into())
+                    };
+
+                    self.c.cm.new_source_file(filename, src.to_string())
+                }
+                Input::FromFilename => {
+                    let filename = &options.swc.filename;
+                    if filename.is_empty() {
+                        bail!("no filename is provided via options");
+                    }
+
+                    self.c.cm.new_source_file(
+                        FileName::Real(filename.into()),
+                        read_to_string(filename).with_context(|| {
+                            format!("Failed to read source code from {}", filename)
+                        })?,
+                    )
+                }
+            };
+            let unresolved_mark = Mark::new();
+            let mut options = options.patch(&fm);
+            options.swc.unresolved_mark = Some(unresolved_mark);
+
+            let cm = self.c.cm.clone();
+            let file = fm.clone();
+
+            let comments = SingleThreadedComments::default();
+            self.c.process_js_with_custom_pass(
+                fm,
+                None,
+                handler,
+                &options.swc,
+                comments.clone(),
+                |_| {
+                    custom_before_pass(
+                        cm,
+                        file,
+                        &options,
+                        comments.clone(),
+                        eliminated_packages,
+                        unresolved_mark,
+                    )
+                },
+                |_| noop(),
+            )
+        })
+    }
+}
+
 impl Task for TransformTask {
-    type Output = (TransformOutput, FxHashSet<String>);
+    type Output = TransformTaskOutput;
     type JsValue = Object;
 
     fn compute(&mut self) -> napi::Result<Self::Output> {
         GLOBALS.set(&Default::default(), || {
+            let options: TransformOptions = serde_json::from_slice(&self.options)
+                .map_err(anyhow::Error::from)
+                .convert_err()?;
             let eliminated_packages: Rc<RefCell<fxhash::FxHashSet<String>>> = Default::default();
             let res = catch_unwind(AssertUnwindSafe(|| {
-                try_with_handler(
-                    self.c.cm.clone(),
-                    turbopack_binding::swc::core::base::HandlerOpts {
-                        color: ColorConfig::Always,
-                        skip_filename: skip_filename(),
-                    },
-                    |handler| {
-                        self.c.run(|| {
-                            let options: TransformOptions = serde_json::from_slice(&self.options)?;
-                            let fm = match &self.input {
-                                Input::Source { src } => {
-                                    let filename = if options.swc.filename.is_empty() {
-                                        FileName::Anon
-                                    } else {
-                                        FileName::Real(options.swc.filename.clone().into())
-                                    };
-
-                                    self.c.cm.new_source_file(filename, src.to_string())
-                                }
-                                Input::FromFilename => {
-                                    let filename = &options.swc.filename;
-                                    if filename.is_empty() {
-                                        bail!("no filename is provided via options");
-                                    }
-
-                                    self.c.cm.new_source_file(
-                                        FileName::Real(filename.into()),
-                                        read_to_string(filename).with_context(|| {
-                                            format!("Failed to read source code from {}", filename)
-                                        })?,
-                                    )
-                                }
-                            };
-                            let unresolved_mark = Mark::new();
-                            let mut options = options.patch(&fm);
-                            options.swc.unresolved_mark = Some(unresolved_mark);
-
-                            let cm = self.c.cm.clone();
-                            let file = fm.clone();
-
-                            let comments = SingleThreadedComments::default();
-                            self.c.process_js_with_custom_pass(
-                                fm,
-                                None,
-                                handler,
-                                &options.swc,
-                                comments.clone(),
-                                |_| {
-                                    custom_before_pass(
-                                        cm,
-                                        file,
-                                        &options,
-                                        comments.clone(),
-                                        eliminated_packages.clone(),
-                                        unresolved_mark,
-                                    )
-                                },
-                                |_| noop(),
-                            )
-                        })
-                    },
-                )
+                if options.json_diagnostics {
+                    let (output, diagnostics) =
+                        try_with_json_handler(self.c.cm.clone(), |handler| {
+                            self.run_transform(handler, options, eliminated_packages.clone())
+                        });
+                    Ok((output, Some(diagnostics)))
+                } else {
+                    try_with_handler(
+                        self.c.cm.clone(),
+                        turbopack_binding::swc::core::base::HandlerOpts {
+                            color: ColorConfig::Always,
+                            skip_filename: skip_filename(),
+                        },
+                        |handler| self.run_transform(handler, options, eliminated_packages.clone()),
+                    )
+                    .map(|output| (Some(output), None))
+                }
             }))
             .map_err(|err| {
                 if let Some(s) = err.downcast_ref::<String>() {
@@ -147,7 +184,11 @@ impl Task for TransformTask {
 
             match res {
                 Ok(res) => res
-                    .map(|o| (o, eliminated_packages.replace(Default::default())))
+                    .map(|(output, diagnostics)| TransformTaskOutput {
+                        output,
+                        eliminated_packages: eliminated_packages.replace(Default::default()),
+                        diagnostics,
+                    })
                     .convert_err(),
                 Err(err) => Err(napi::Error::new(
                     Status::GenericFailure,
@@ -160,9 +201,20 @@ impl Task for TransformTask {
     fn resolve(
         &mut self,
         env: Env,
-        (output, eliminated_packages): Self::Output,
+        TransformTaskOutput {
+            output,
+            eliminated_packages,
+            diagnostics,
+        }: Self::Output,
     ) -> napi::Result<Self::JsValue> {
-        complete_output(&env, output, eliminated_packages)
+        let mut js_output = match output {
+            Some(output) => complete_output(&env, output, eliminated_packages)?,
+            None => env.create_object()?,
+        };
+        if let Some(diagnostics) = diagnostics {
+            js_output.set_named_property("diagnostics", env.to_js_value(&diagnostics)?)?;
+        }
+        Ok(js_output)
     }
 }
 
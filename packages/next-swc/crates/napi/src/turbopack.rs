@@ -7,6 +7,7 @@ use next_build::{
     BuildOptions as NextBuildOptions,
 };
 use next_core::next_config::{Rewrite, Rewrites, RouteHas};
+use turbopack_binding::turbopack::core::error::PrettyPrintError;
 
 use crate::next_api::project::NapiDefineEnv;
 
@@ -199,6 +200,9 @@ impl From<NapiRouteHas> for RouteHas {
 }
 
 #[napi]
-pub async fn experimental_turbo(_unused: Buffer) -> napi::Result<()> {
-    unimplemented!("__experimental_turbo is not yet implemented");
+pub async fn experimental_turbo(ctx: NextBuildContext) -> napi::Result<()> {
+    let options = NextBuildOptions::try_from(ctx)?;
+    next_build::build(options)
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))
 }
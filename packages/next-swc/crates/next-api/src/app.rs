@@ -23,19 +23,24 @@ use next_core::{
     next_dynamic::NextDynamicTransition,
     next_edge::route_regex::get_named_middleware_regex,
     next_manifests::{
-        AppBuildManifest, AppPathsManifest, BuildManifest, ClientReferenceManifest,
-        EdgeFunctionDefinition, LoadableManifest, MiddlewareMatcher, MiddlewaresManifestV2,
-        PagesManifest, Regions,
+        client_assets_integrity, subresource_integrity_manifest, AppBuildManifest,
+        AppPathsManifest, BuildManifest, ClientReferenceManifest, EdgeFunctionDefinition,
+        FunctionConfig, FunctionsConfigManifest, LoadableManifest, MiddlewareMatcher,
+        MiddlewaresManifestV2, PagesManifest, Regions,
     },
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
         get_server_runtime_entries, ServerContextType,
     },
+    parse_segment_config_from_loader_tree, parse_segment_exports_from_loader_tree,
     util::{get_asset_prefix_from_pathname, NextRuntime},
+    NextSegmentConfig,
 };
 use serde::{Deserialize, Serialize};
 use tracing::Instrument;
-use turbo_tasks::{trace::TraceRawVcs, Completion, TryFlatJoinIterExt, TryJoinIterExt, Value, Vc};
+use turbo_tasks::{
+    trace::TraceRawVcs, Completion, Completions, TryFlatJoinIterExt, TryJoinIterExt, Value, Vc,
+};
 use turbopack_binding::{
     turbo::{
         tasks_env::{CustomProcessEnv, ProcessEnv},
@@ -63,11 +68,12 @@ use crate::{
         collect_chunk_group, collect_evaluated_chunk_group, collect_next_dynamic_imports,
         DynamicImportedChunks,
     },
+    export::validate_app_page_export,
     font::create_font_manifest,
     middleware::{get_js_paths_from_root, get_wasm_paths_from_root, wasm_paths_to_bindings},
     project::Project,
     route::{Endpoint, Route, Routes, WrittenEndpoint},
-    server_actions::create_server_actions_manifest,
+    server_actions::{create_server_actions_manifest, validate_server_actions_export},
     server_paths::all_server_paths,
 };
 
@@ -133,10 +139,27 @@ impl AppProject {
     }
 
     #[turbo_tasks::function]
-    fn app_entrypoints(&self) -> Vc<AppEntrypoints> {
+    pub(super) fn app_entrypoints(&self) -> Vc<AppEntrypoints> {
         get_entrypoints(self.app_dir, self.project.next_config().page_extensions())
     }
 
+    /// Reports the pages which can't be used with `output: 'export'`.
+    #[turbo_tasks::function]
+    pub async fn validate_export(self: Vc<Self>) -> Result<Vc<Completion>> {
+        let completions = self
+            .app_entrypoints()
+            .await?
+            .iter()
+            .filter_map(|(pathname, entrypoint)| match entrypoint {
+                AppEntrypoint::AppPage { loader_tree, .. } => {
+                    Some(validate_app_page_export(pathname.clone(), *loader_tree))
+                }
+                _ => None,
+            })
+            .collect();
+        Ok(Vc::<Completions>::cell(completions).completed())
+    }
+
     #[turbo_tasks::function]
     async fn client_module_options_context(self: Vc<Self>) -> Result<Vc<ModuleOptionsContext>> {
         let this = self.await?;
@@ -429,6 +452,8 @@ pub async fn app_entry_point_to_route(
                 }
                 .cell(),
             ),
+            config: parse_segment_config_from_loader_tree(loader_tree),
+            segment_exports: parse_segment_exports_from_loader_tree(loader_tree),
         },
         AppEntrypoint::AppRoute { page, path } => Route::AppRoute {
             endpoint: Vc::upcast(
@@ -484,15 +509,16 @@ struct AppEndpoint {
 #[turbo_tasks::value_impl]
 impl AppEndpoint {
     #[turbo_tasks::function]
-    fn app_page_entry(&self, loader_tree: Vc<LoaderTree>) -> Vc<AppEntry> {
-        get_app_page_entry(
+    async fn app_page_entry(&self, loader_tree: Vc<LoaderTree>) -> Result<Vc<AppEntry>> {
+        Ok(get_app_page_entry(
             self.app_project.rsc_module_context(),
             self.app_project.edge_rsc_module_context(),
             loader_tree,
             self.page.clone(),
             self.app_project.project().project_path(),
             self.app_project.project().next_config(),
-        )
+            *self.app_project.mode().await?,
+        ))
     }
 
     #[turbo_tasks::function]
@@ -554,6 +580,7 @@ impl AppEndpoint {
         let mut client_assets = vec![];
         // assets to add to the middleware manifest (to be loaded in the edge runtime).
         let mut middleware_assets = vec![];
+        let mut has_integrity_manifest = false;
 
         let app_entry = app_entry.await?;
 
@@ -674,7 +701,22 @@ impl AppEndpoint {
                 .collect::<anyhow::Result<Vec<_>>>()?;
             entry_client_chunks_paths.extend(client_shared_chunks_paths.iter().cloned());
 
+            let integrity = client_assets_integrity(
+                client_relative_path,
+                OutputAssets::new(client_assets.clone()),
+                this.app_project.project().next_config(),
+                *this.app_project.mode().await?,
+            );
+            let integrity_ref = integrity.await?;
+            let integrity_of = |paths: &[String]| {
+                paths
+                    .iter()
+                    .filter_map(|path| Some((path.clone(), integrity_ref.get(path)?.clone())))
+                    .collect()
+            };
+
             let app_build_manifest = AppBuildManifest {
+                integrity: integrity_of(&entry_client_chunks_paths),
                 pages: [(app_entry.original_name.clone(), entry_client_chunks_paths)]
                     .into_iter()
                     .collect(),
@@ -691,6 +733,7 @@ impl AppEndpoint {
             server_assets.push(app_build_manifest_output);
 
             let build_manifest = BuildManifest {
+                integrity: integrity_of(&client_shared_chunks_paths),
                 root_main_files: client_shared_chunks_paths,
                 ..Default::default()
             };
@@ -717,6 +760,16 @@ impl AppEndpoint {
             );
             server_assets.push(entry_manifest);
 
+            let integrity_manifest = subresource_integrity_manifest(
+                node_root.join(format!(
+                    "server/app{manifest_path_prefix}/{ty}/subresource-integrity-manifest.json",
+                )),
+                integrity,
+            )
+            .await?;
+            has_integrity_manifest = !integrity_manifest.is_empty();
+            server_assets.extend(integrity_manifest.iter().copied());
+
             if runtime == NextRuntime::Edge {
                 middleware_assets.push(entry_manifest);
 
@@ -767,6 +820,35 @@ impl AppEndpoint {
             )))
         }
 
+        fn create_functions_config_manifest(
+            node_root: Vc<FileSystemPath>,
+            ty: &'static str,
+            pathname: &str,
+            original_name: &str,
+            config: &NextSegmentConfig,
+        ) -> Result<Vc<Box<dyn OutputAsset>>> {
+            let manifest_path_prefix = get_asset_prefix_from_pathname(pathname);
+            let path = node_root.join(format!(
+                "server/app{manifest_path_prefix}/{ty}/functions-config-manifest.json",
+            ));
+            let mut functions_config_manifest = FunctionsConfigManifest::default();
+            if config.max_duration.is_some() || config.experimental_ppr.is_some() {
+                functions_config_manifest.functions.insert(
+                    original_name.to_string(),
+                    FunctionConfig {
+                        max_duration: config.max_duration,
+                        experimental_ppr: config.experimental_ppr,
+                    },
+                );
+            }
+            Ok(Vc::upcast(VirtualOutputAsset::new(
+                path,
+                AssetContent::file(
+                    File::from(serde_json::to_string_pretty(&functions_config_manifest)?).into(),
+                ),
+            )))
+        }
+
         async fn create_react_loadable_manifest(
             dynamic_import_entries: Vc<DynamicImportedChunks>,
             ty: &'static str,
@@ -858,6 +940,14 @@ impl AppEndpoint {
                 evaluatable_assets.push(evaluatable);
 
                 if let Some(app_server_reference_modules) = app_server_reference_modules {
+                    if *this.app_project.project().next_config().is_export().await? {
+                        validate_server_actions_export(
+                            Vc::upcast(app_entry.rsc_entry),
+                            app_server_reference_modules,
+                            Vc::upcast(this.app_project.edge_rsc_module_context()),
+                        )
+                        .await?;
+                    }
                     let (loader, manifest) = create_server_actions_manifest(
                         Vc::upcast(app_entry.rsc_entry),
                         app_server_reference_modules,
@@ -894,6 +984,10 @@ impl AppEndpoint {
                     "server/next-font-manifest.js".to_string(),
                     "server/interception-route-rewrite-manifest.js".to_string(),
                 ];
+                if has_integrity_manifest {
+                    file_paths_from_root
+                        .push("server/subresource-integrity-manifest.js".to_string());
+                }
                 let mut wasm_paths_from_root = vec![];
 
                 let node_root_value = node_root.await?;
@@ -920,18 +1014,16 @@ impl AppEndpoint {
                     original_source: app_entry.pathname.clone(),
                     ..Default::default()
                 };
+                let config = app_entry.config.await?;
                 let edge_function_definition = EdgeFunctionDefinition {
                     files: file_paths_from_root,
                     wasm: wasm_paths_to_bindings(wasm_paths_from_root),
                     name: app_entry.pathname.to_string(),
                     page: app_entry.original_name.clone(),
-                    regions: app_entry
-                        .config
-                        .await?
-                        .preferred_region
-                        .clone()
-                        .map(Regions::Multiple),
+                    regions: config.preferred_region.clone().map(Regions::Multiple),
                     matchers: vec![matchers],
+                    max_duration: config.max_duration,
+                    experimental_ppr: config.experimental_ppr,
                     ..Default::default()
                 };
                 let middleware_manifest_v2 = MiddlewaresManifestV2 {
@@ -994,6 +1086,14 @@ impl AppEndpoint {
                     this.app_project.rsc_runtime_entries().await?.clone_value();
 
                 if let Some(app_server_reference_modules) = app_server_reference_modules {
+                    if *this.app_project.project().next_config().is_export().await? {
+                        validate_server_actions_export(
+                            Vc::upcast(app_entry.rsc_entry),
+                            app_server_reference_modules,
+                            Vc::upcast(this.app_project.rsc_module_context()),
+                        )
+                        .await?;
+                    }
                     let (loader, manifest) = create_server_actions_manifest(
                         Vc::upcast(app_entry.rsc_entry),
                         app_server_reference_modules,
@@ -1041,6 +1141,15 @@ impl AppEndpoint {
                 )?;
                 server_assets.push(app_paths_manifest_output);
 
+                let functions_config_manifest_output = create_functions_config_manifest(
+                    node_root,
+                    ty,
+                    &app_entry.pathname,
+                    &app_entry.original_name,
+                    &*app_entry.config.await?,
+                )?;
+                server_assets.push(functions_config_manifest_output);
+
                 // create react-loadable-manifest for next/dynamic
                 let availability_info = Value::new(AvailabilityInfo::Root);
                 let dynamic_import_modules =
@@ -0,0 +1,199 @@
+//! Validation of `output: 'export'`, which requires every route to be
+//! renderable to static files at build time.
+
+use anyhow::Result;
+use next_core::{
+    app_structure::LoaderTree, export_issue_source, next_edge::route_regex::is_dynamic_route,
+    pages_structure::PagesStructure, parse_segment_config_from_source,
+    parse_segment_exports_from_loader_tree, NextSegmentDynamic,
+};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, Completion, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{
+        file_source::FileSource,
+        issue::{
+            Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource, OptionStyledString,
+            StyledString,
+        },
+    },
+};
+
+/// A feature which needs a server at runtime and can't be used with
+/// `output: 'export'`.
+#[derive(Clone, Debug, PartialEq, Eq, TraceRawVcs, Serialize, Deserialize)]
+pub enum UnsupportedExportFeature {
+    Middleware,
+    ForceDynamic,
+    /// A dynamic route without `generateStaticParams`, so its paths can't be
+    /// known at build time.
+    MissingGenerateStaticParams {
+        pathname: String,
+    },
+    ApiRoute,
+    ServerActions,
+}
+
+#[turbo_tasks::value(shared)]
+pub struct ExportIssue {
+    path: Vc<FileSystemPath>,
+    feature: UnsupportedExportFeature,
+    source: Vc<IssueSource>,
+}
+
+impl ExportIssue {
+    pub(crate) fn new(
+        path: Vc<FileSystemPath>,
+        feature: UnsupportedExportFeature,
+        source: Vc<IssueSource>,
+    ) -> Vc<Self> {
+        ExportIssue {
+            path,
+            feature,
+            source,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ExportIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("export".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        let title = match &self.feature {
+            UnsupportedExportFeature::Middleware => {
+                "Middleware cannot be used with \"output: export\"".to_string()
+            }
+            UnsupportedExportFeature::ForceDynamic => {
+                "Page with `dynamic = \"force-dynamic\"` couldn't be exported".to_string()
+            }
+            UnsupportedExportFeature::MissingGenerateStaticParams { pathname } => format!(
+                "Page \"{pathname}\" is missing \"generateStaticParams()\" so it cannot be used \
+                 with \"output: export\" config"
+            ),
+            UnsupportedExportFeature::ApiRoute => {
+                "API Routes cannot be used with \"output: export\"".to_string()
+            }
+            UnsupportedExportFeature::ServerActions => {
+                "Server Actions are not supported with static export".to_string()
+            }
+        };
+        StyledString::Text(title).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                "`output: \"export\"` requires all pages to be renderable statically, because \
+                 there is no server to handle requests at runtime in this output format."
+                    .to_string(),
+            )
+            .cell(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell(
+            "https://nextjs.org/docs/app/building-your-application/deploying/static-exports#unsupported-features"
+                .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(Some(self.source))
+    }
+}
+
+/// Reports the segments of an app page which prevent it from being exported.
+#[turbo_tasks::function]
+pub(crate) async fn validate_app_page_export(
+    pathname: String,
+    loader_tree: Vc<LoaderTree>,
+) -> Result<Vc<Completion>> {
+    let mut page = None;
+    let mut queue = vec![loader_tree];
+    while let Some(tree) = queue.pop() {
+        let tree = tree.await?;
+        let components = tree.components.await?;
+        page = page.or(components.page);
+        for component in [components.page, components.default, components.layout]
+            .into_iter()
+            .flatten()
+        {
+            let source = Vc::upcast(FileSource::new(component));
+            let config = parse_segment_config_from_source(source).await?;
+            if config.dynamic == Some(NextSegmentDynamic::ForceDynamic) {
+                ExportIssue::new(
+                    component,
+                    UnsupportedExportFeature::ForceDynamic,
+                    export_issue_source(source, "dynamic".to_string()),
+                )
+                .emit();
+            }
+        }
+        queue.extend(tree.parallel_routes.values().copied());
+    }
+
+    if is_dynamic_route(&pathname)
+        && !*parse_segment_exports_from_loader_tree(loader_tree)
+            .has_generate_static_params()
+            .await?
+    {
+        if let Some(page) = page {
+            ExportIssue::new(
+                page,
+                UnsupportedExportFeature::MissingGenerateStaticParams { pathname },
+                export_issue_source(Vc::upcast(FileSource::new(page)), "default".to_string()),
+            )
+            .emit();
+        }
+    }
+
+    Ok(Completion::immutable())
+}
+
+/// Reports every API route of the pages router, as API routes need a server.
+#[turbo_tasks::function]
+pub(crate) async fn validate_pages_export(
+    pages_structure: Vc<PagesStructure>,
+) -> Result<Vc<Completion>> {
+    let Some(api) = pages_structure.await?.api else {
+        return Ok(Completion::immutable());
+    };
+
+    let mut queue = vec![api];
+    while let Some(dir) = queue.pop() {
+        let dir = dir.await?;
+        for item in dir.items.iter() {
+            let path = item.await?.project_path;
+            ExportIssue::new(
+                path,
+                UnsupportedExportFeature::ApiRoute,
+                export_issue_source(Vc::upcast(FileSource::new(path)), "default".to_string()),
+            )
+            .emit();
+        }
+        queue.extend(dir.children.iter().copied());
+    }
+
+    Ok(Completion::immutable())
+}
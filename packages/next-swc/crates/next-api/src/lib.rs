@@ -4,10 +4,12 @@
 mod app;
 mod dynamic_imports;
 pub mod entrypoints;
+mod export;
 mod font;
 mod instrumentation;
 mod middleware;
 mod pages;
+pub mod prerender_manifest;
 pub mod project;
 pub mod route;
 mod server_actions;
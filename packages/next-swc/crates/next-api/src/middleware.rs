@@ -1,12 +1,9 @@
 use anyhow::{bail, Context, Result};
 use next_core::{
     all_assets_from_entries,
-    middleware::get_middleware_module,
-    mode::NextMode,
+    middleware::{get_middleware_matchers, get_middleware_module},
     next_edge::entry::wrap_edge_entry,
-    next_manifests::{
-        AssetBinding, EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2,
-    },
+    next_manifests::{AssetBinding, EdgeFunctionDefinition, MiddlewaresManifestV2},
     next_server::{get_server_runtime_entries, ServerContextType},
     util::parse_config_from_source,
 };
@@ -80,7 +77,7 @@ impl MiddlewareEndpoint {
 
         let mut evaluatable_assets = get_server_runtime_entries(
             Value::new(ServerContextType::Middleware),
-            NextMode::Development,
+            *self.project.next_mode().await?,
         )
         .resolve_entries(self.context)
         .await?
@@ -135,21 +132,10 @@ impl MiddlewareEndpoint {
         let wasm_paths_from_root =
             get_wasm_paths_from_root(&node_root_value, &all_output_assets).await?;
 
-        let matchers = if let Some(matchers) = config.await?.matcher.as_ref() {
-            matchers
-                .iter()
-                .map(|matcher| MiddlewareMatcher {
-                    original_source: matcher.to_string(),
-                    ..Default::default()
-                })
-                .collect()
-        } else {
-            vec![MiddlewareMatcher {
-                regexp: Some("^/.*$".to_string()),
-                original_source: "/:path*".to_string(),
-                ..Default::default()
-            }]
-        };
+        let matchers = get_middleware_matchers(
+            config.await?.matcher.as_deref(),
+            &this.project.next_config().await?,
+        )?;
 
         let edge_function_definition = EdgeFunctionDefinition {
             files: file_paths_from_root,
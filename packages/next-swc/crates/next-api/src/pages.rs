@@ -13,8 +13,9 @@ use next_core::{
     next_dynamic::NextDynamicTransition,
     next_edge::route_regex::get_named_middleware_regex,
     next_manifests::{
-        BuildManifest, EdgeFunctionDefinition, LoadableManifest, MiddlewareMatcher,
-        MiddlewaresManifestV2, PagesManifest,
+        client_assets_integrity, subresource_integrity_manifest, AssetsIntegrity, BuildManifest,
+        EdgeFunctionDefinition, LoadableManifest, MiddlewareMatcher, MiddlewaresManifestV2,
+        PagesManifest,
     },
     next_pages::create_page_ssr_entry_module,
     next_server::{
@@ -24,6 +25,7 @@ use next_core::{
     pages_structure::{
         find_pages_structure, PagesDirectoryStructure, PagesStructure, PagesStructureItem,
     },
+    parse_segment_exports_from_source,
     util::{get_asset_prefix_from_pathname, parse_config_from_source, NextRuntime},
     PageLoaderAsset,
 };
@@ -72,6 +74,7 @@ use crate::{
         collect_chunk_group, collect_evaluated_chunk_group, collect_next_dynamic_imports,
         DynamicImportedChunks,
     },
+    export::validate_pages_export,
     font::create_font_manifest,
     middleware::{get_js_paths_from_root, get_wasm_paths_from_root, wasm_paths_to_bindings},
     project::Project,
@@ -178,6 +181,7 @@ impl PagesProject {
                 path,
                 pages_structure,
             )),
+            exports: parse_segment_exports_from_source(Vc::upcast(FileSource::new(path))),
         };
 
         if let Some(pages) = pages {
@@ -237,7 +241,18 @@ impl PagesProject {
     }
 
     #[turbo_tasks::function]
-    fn pages_structure(&self) -> Vc<PagesStructure> {
+    fn mode(&self) -> Vc<NextMode> {
+        self.mode.cell()
+    }
+
+    /// Reports the routes which can't be used with `output: 'export'`.
+    #[turbo_tasks::function]
+    pub fn validate_export(self: Vc<Self>) -> Vc<Completion> {
+        validate_pages_export(self.pages_structure())
+    }
+
+    #[turbo_tasks::function]
+    pub(super) fn pages_structure(&self) -> Vc<PagesStructure> {
         let next_router_fs = Vc::upcast::<Box<dyn FileSystem>>(VirtualFileSystem::new());
         let next_router_root = next_router_fs.root();
         find_pages_structure(
@@ -675,6 +690,7 @@ impl PageEndpoint {
                     this.pages_structure,
                     config.runtime,
                     this.pages_project.project().next_config(),
+                    *this.pages_project.mode().await?,
                 );
 
                 let mut evaluatable_assets = edge_runtime_entries.await?.clone_value();
@@ -727,6 +743,7 @@ impl PageEndpoint {
                         this.pages_structure,
                         config.runtime,
                         this.pages_project.project().next_config(),
+                        *this.pages_project.mode().await?,
                     )
                 };
 
@@ -924,6 +941,36 @@ impl PageEndpoint {
         Ok(Vc::cell(output))
     }
 
+    #[turbo_tasks::function]
+    async fn client_chunks_integrity(
+        self: Vc<Self>,
+        client_chunks: Vc<OutputAssets>,
+    ) -> Result<Vc<AssetsIntegrity>> {
+        let this = self.await?;
+        Ok(client_assets_integrity(
+            this.pages_project.project().client_relative_path(),
+            client_chunks,
+            this.pages_project.project().next_config(),
+            *this.pages_project.mode().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn subresource_integrity_manifest(
+        self: Vc<Self>,
+        client_chunks: Vc<OutputAssets>,
+    ) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let node_root = this.pages_project.project().node_root();
+        let manifest_path_prefix = get_asset_prefix_from_pathname(&this.pathname.await?);
+        Ok(subresource_integrity_manifest(
+            node_root.join(format!(
+                "server/pages{manifest_path_prefix}/subresource-integrity-manifest.json",
+            )),
+            self.client_chunks_integrity(client_chunks),
+        ))
+    }
+
     #[turbo_tasks::function]
     async fn build_manifest(
         self: Vc<Self>,
@@ -933,28 +980,31 @@ impl PageEndpoint {
         let node_root = this.pages_project.project().node_root();
         let client_relative_path = this.pages_project.project().client_relative_path();
         let client_relative_path_ref = client_relative_path.await?;
+        let client_chunks_paths = client_chunks
+            .await?
+            .iter()
+            .copied()
+            .map(|chunk| {
+                let client_relative_path_ref = client_relative_path_ref.clone();
+                async move {
+                    let chunk_path = chunk.ident().path().await?;
+                    Ok(client_relative_path_ref
+                        .get_path_to(&chunk_path)
+                        .context("client chunk entry path must be inside the client root")?
+                        .to_string())
+                }
+            })
+            .try_join()
+            .await?;
+        let integrity = self.client_chunks_integrity(client_chunks).await?;
         let build_manifest = BuildManifest {
-            pages: [(
-                this.pathname.await?.clone_value(),
-                client_chunks
-                    .await?
-                    .iter()
-                    .copied()
-                    .map(|chunk| {
-                        let client_relative_path_ref = client_relative_path_ref.clone();
-                        async move {
-                            let chunk_path = chunk.ident().path().await?;
-                            Ok(client_relative_path_ref
-                                .get_path_to(&chunk_path)
-                                .context("client chunk entry path must be inside the client root")?
-                                .to_string())
-                        }
-                    })
-                    .try_join()
-                    .await?,
-            )]
-            .into_iter()
-            .collect(),
+            integrity: client_chunks_paths
+                .iter()
+                .filter_map(|path| Some((path.clone(), integrity.get(path)?.clone())))
+                .collect(),
+            pages: [(this.pathname.await?.clone_value(), client_chunks_paths)]
+                .into_iter()
+                .collect(),
             ..Default::default()
         };
         let manifest_path_prefix = get_asset_prefix_from_pathname(&this.pathname.await?);
@@ -984,6 +1034,12 @@ impl PageEndpoint {
                 client_assets.extend(client_chunks.await?.iter().copied());
                 let build_manifest = self.build_manifest(client_chunks);
                 server_assets.push(build_manifest);
+                server_assets.extend(
+                    self.subresource_integrity_manifest(client_chunks)
+                        .await?
+                        .iter()
+                        .copied(),
+                );
                 self.ssr_chunk()
             }
             PageEndpointType::Data => self.ssr_data_chunk(),
@@ -1049,6 +1105,15 @@ impl PageEndpoint {
                     "server/middleware-react-loadable-manifest.js".to_string(),
                     "server/next-font-manifest.js".to_string(),
                 ];
+                if matches!(this.ty, PageEndpointType::Html | PageEndpointType::Data)
+                    && !self
+                        .client_chunks_integrity(self.client_chunks())
+                        .await?
+                        .is_empty()
+                {
+                    file_paths_from_root
+                        .push("server/subresource-integrity-manifest.js".to_string());
+                }
                 let mut wasm_paths_from_root = vec![];
 
                 let node_root_value = node_root.await?;
@@ -1064,6 +1129,14 @@ impl PageEndpoint {
                 let named_regex = get_named_middleware_regex(&pathname);
                 let matchers = MiddlewareMatcher {
                     regexp: Some(named_regex),
+                    // Pages are matched against the pathname without its locale.
+                    locale: this
+                        .pages_project
+                        .project()
+                        .next_config()
+                        .await?
+                        .i18n
+                        .is_some(),
                     original_source: pathname.to_string(),
                     ..Default::default()
                 };
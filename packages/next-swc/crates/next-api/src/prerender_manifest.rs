@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use next_core::{
+    next_config::NextConfig,
+    next_edge::route_regex::{get_route_regex, is_dynamic_route},
+    next_manifests::{DynamicSsgRoute, PrerenderManifest, PreviewProps, SsgRoute},
+    NextRevalidate, NextSegmentDynamic,
+};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::{File, FileSystemPath},
+    turbopack::core::{
+        asset::AssetContent,
+        output::{OutputAsset, OutputAssets},
+        virtual_output::VirtualOutputAsset,
+    },
+};
+
+use crate::route::{Route, Routes};
+
+/// Builds the `prerender-manifest.json` from the segment configs of the app
+/// router's pages and the data fetching exports of the pages router's pages,
+/// along with the `prerender-manifest.js` edge functions read the preview mode
+/// secrets from.
+///
+/// Nothing is prerendered by the build itself, so the pages are listed to be
+/// rendered and cached on their first request:
+/// - app pages with `dynamic = 'force-static'`, `dynamic = 'error'`, which
+///   fails on request data, or a non-zero `revalidate`,
+/// - pages with `getStaticProps`, which revalidate as often as it returns,
+///   below each of their i18n locales.
+///
+/// Dynamic app pages without `generateStaticParams` are left out.
+#[turbo_tasks::function]
+pub async fn build_prerender_manifest(
+    node_root: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+    routes: Vc<Routes>,
+    build_id: String,
+    preview_props: PreviewProps,
+) -> Result<Vc<OutputAssets>> {
+    let next_config = next_config.await?;
+    let mut static_routes = HashMap::new();
+    let mut dynamic_routes = HashMap::new();
+
+    for (pathname, route) in routes.await?.iter() {
+        match *route {
+            Route::AppPage {
+                config,
+                segment_exports,
+                ..
+            } => {
+                let config = config.await?;
+                let is_static = match config.dynamic {
+                    Some(NextSegmentDynamic::ForceStatic | NextSegmentDynamic::Error) => true,
+                    Some(NextSegmentDynamic::ForceDynamic) => false,
+                    _ => config.revalidate.is_some(),
+                };
+                if !is_static || config.revalidate == Some(NextRevalidate::Frequency { seconds: 0 })
+                {
+                    continue;
+                }
+
+                let data_route = if pathname == "/" {
+                    "/index.rsc".to_string()
+                } else {
+                    format!("{pathname}.rsc")
+                };
+
+                if is_dynamic_route(pathname) {
+                    if !*segment_exports.has_generate_static_params().await? {
+                        continue;
+                    }
+                    let route_regex = get_route_regex(pathname).regex;
+                    let data_route_regex = format!(
+                        "{}\\.rsc$",
+                        route_regex.strip_suffix("(?:/)?$").unwrap_or(&route_regex)
+                    );
+                    dynamic_routes.insert(
+                        pathname.clone(),
+                        DynamicSsgRoute {
+                            route_regex,
+                            data_route: Some(data_route),
+                            data_route_regex: Some(data_route_regex),
+                            fallback: (config.dynamic_params == Some(false)).then_some(false),
+                            experimental_ppr: config.experimental_ppr,
+                        },
+                    );
+                } else {
+                    static_routes.insert(
+                        pathname.clone(),
+                        SsgRoute {
+                            initial_revalidate_seconds: config.revalidate.unwrap_or_default(),
+                            src_route: None,
+                            data_route: Some(data_route),
+                            experimental_ppr: config.experimental_ppr,
+                        },
+                    );
+                }
+            }
+            Route::Page { exports, .. } => {
+                let exports = exports.await?;
+                if !exports.get_static_props || exports.get_server_side_props {
+                    continue;
+                }
+
+                if is_dynamic_route(pathname) {
+                    let route_regex = get_route_regex(pathname).regex;
+                    let data_route_regex =
+                        get_route_regex(&format!("/_next/data/{build_id}{pathname}")).regex;
+                    let data_route_regex = format!(
+                        "{}\\.json$",
+                        data_route_regex
+                            .strip_suffix("(?:/)?$")
+                            .unwrap_or(&data_route_regex)
+                    );
+                    dynamic_routes.insert(
+                        pathname.clone(),
+                        DynamicSsgRoute {
+                            route_regex,
+                            data_route: Some(format!("/_next/data/{build_id}{pathname}.json")),
+                            data_route_regex: Some(data_route_regex),
+                            // The paths returned by `getStaticPaths` aren't known, so every path
+                            // is rendered on its first request.
+                            fallback: None,
+                            experimental_ppr: None,
+                        },
+                    );
+                } else {
+                    // With i18n, the page is rendered and cached once per locale.
+                    let pathnames = match &next_config.i18n {
+                        Some(i18n) => i18n.localized_pathnames(pathname).collect(),
+                        None => vec![pathname.clone()],
+                    };
+                    for pathname in pathnames {
+                        let page_path = if pathname == "/" { "/index" } else { &pathname };
+                        let data_route = format!("/_next/data/{build_id}{page_path}.json");
+                        static_routes.insert(
+                            pathname,
+                            SsgRoute {
+                                // The revalidate `getStaticProps` returns replaces this once the
+                                // page has been rendered.
+                                initial_revalidate_seconds: NextRevalidate::Never,
+                                src_route: None,
+                                data_route: Some(data_route),
+                                experimental_ppr: None,
+                            },
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let prerender_manifest = PrerenderManifest {
+        version: 4,
+        routes: static_routes,
+        dynamic_routes,
+        not_found_routes: vec![],
+        preview: preview_props,
+    };
+    let json = serde_json::to_string_pretty(&prerender_manifest)?;
+    let js = format!(
+        "self.__PRERENDER_MANIFEST={}",
+        serde_json::to_string(&serde_json::to_string(&prerender_manifest)?)?
+    );
+
+    Ok(Vc::cell(vec![
+        Vc::upcast(VirtualOutputAsset::new(
+            node_root.join("prerender-manifest.json".to_string()),
+            AssetContent::file(File::from(json).into()),
+        )),
+        Vc::upcast(VirtualOutputAsset::new(
+            node_root.join("prerender-manifest.js".to_string()),
+            AssetContent::file(File::from(js).into()),
+        )),
+    ]))
+}
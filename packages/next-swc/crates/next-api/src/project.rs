@@ -5,7 +5,7 @@ use indexmap::{map::Entry, IndexMap};
 use next_core::{
     all_assets_from_entries,
     app_structure::find_app_dir,
-    emit_assets, get_edge_chunking_context, get_edge_compile_time_info,
+    emit_assets, export_issue_source, get_edge_chunking_context, get_edge_compile_time_info,
     get_edge_resolve_options_context,
     instrumentation::instrumentation_files,
     middleware::middleware_files,
@@ -17,6 +17,9 @@ use next_core::{
         get_server_module_options_context, get_server_resolve_options_context, ServerContextType,
     },
     next_telemetry::NextFeatureTelemetry,
+    typed_routes::{
+        app_typed_routes, build_typed_routes_declaration, custom_typed_routes, pages_typed_routes,
+    },
 };
 use serde::{Deserialize, Serialize};
 use tracing::Instrument;
@@ -40,6 +43,7 @@ use turbopack_binding::{
             context::AssetContext,
             diagnostics::DiagnosticExt,
             file_source::FileSource,
+            issue::IssueExt,
             output::{OutputAsset, OutputAssets},
             resolve::{find_context_file, FindContextFileResult},
             source::Source,
@@ -58,6 +62,7 @@ use crate::{
     app::{AppProject, OptionAppProject},
     build,
     entrypoints::Entrypoints,
+    export::{ExportIssue, UnsupportedExportFeature},
     instrumentation::InstrumentationEndpoint,
     middleware::MiddlewareEndpoint,
     pages::PagesProject,
@@ -90,6 +95,9 @@ pub struct ProjectOptions {
 
     /// Whether to watch the filesystem for file changes.
     pub watch: bool,
+
+    /// Whether the project is built for `next dev` or for `next build`.
+    pub dev: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, TaskInput, PartialEq, Eq, TraceRawVcs)]
@@ -190,7 +198,7 @@ impl ProjectContainer {
     pub async fn project(self: Vc<Self>) -> Result<Vc<Project>> {
         let this = self.await?;
 
-        let (env, define_env, next_config, js_config, root_path, project_path, watch) = {
+        let (env, define_env, next_config, js_config, root_path, project_path, watch, dev) = {
             let options = this.options_state.get();
             let env: Vc<EnvMap> = Vc::cell(options.env.iter().cloned().collect());
             let define_env: Vc<ProjectDefineEnv> = ProjectDefineEnv {
@@ -204,6 +212,7 @@ impl ProjectContainer {
             let root_path = options.root_path.clone();
             let project_path = options.project_path.clone();
             let watch = options.watch;
+            let dev = options.dev;
             (
                 env,
                 define_env,
@@ -212,6 +221,7 @@ impl ProjectContainer {
                 root_path,
                 project_path,
                 watch,
+                dev,
             )
         };
 
@@ -233,7 +243,11 @@ impl ProjectContainer {
             browserslist_query: "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari \
                                  versions, last 1 Edge versions"
                 .to_string(),
-            mode: NextMode::Development,
+            mode: if dev {
+                NextMode::Development
+            } else {
+                NextMode::Build
+            },
             versioned_content_map: this.versioned_content_map,
         }
         .cell())
@@ -440,6 +454,11 @@ impl Project {
         Ok(self.await?.js_config)
     }
 
+    #[turbo_tasks::function]
+    pub(super) async fn next_mode(self: Vc<Self>) -> Result<Vc<NextMode>> {
+        Ok(self.await?.mode.cell())
+    }
+
     #[turbo_tasks::function]
     pub(super) fn execution_context(self: Vc<Self>) -> Vc<ExecutionContext> {
         let node_root = self.node_root();
@@ -608,6 +627,7 @@ impl Project {
     #[turbo_tasks::function]
     pub async fn entrypoints(self: Vc<Self>) -> Result<Vc<Entrypoints>> {
         self.collect_project_feature_telemetry().await?;
+        self.emit_typed_routes().await?;
 
         let mut routes = IndexMap::new();
         let app_project = self.app_project();
@@ -629,6 +649,13 @@ impl Project {
             }
         }
 
+        if *self.next_config().is_export().await? {
+            if let Some(app_project) = &*app_project.await? {
+                app_project.validate_export().await?;
+            }
+            pages_project.validate_export().await?;
+        }
+
         let pages_document_endpoint = TraitRef::cell(
             self.pages_project()
                 .document_endpoint()
@@ -650,6 +677,14 @@ impl Project {
         );
         let middleware = if let FindContextFileResult::Found(fs_path, _) = *middleware.await? {
             let source = Vc::upcast(FileSource::new(fs_path));
+            if *self.next_config().is_export().await? {
+                ExportIssue::new(
+                    fs_path,
+                    UnsupportedExportFeature::Middleware,
+                    export_issue_source(source, "middleware".to_string()),
+                )
+                .emit();
+            }
             Some(Middleware {
                 endpoint: TraitRef::cell(
                     Vc::upcast::<Box<dyn Endpoint>>(self.middleware_endpoint(source))
@@ -696,27 +731,69 @@ impl Project {
         .cell())
     }
 
+    /// Writes the `types/link.d.ts` declaration of all routes when
+    /// `experimental.typedRoutes` is enabled. As the routes are read from the
+    /// app and pages structures, it's rewritten whenever they change.
     #[turbo_tasks::function]
-    fn middleware_context(self: Vc<Self>) -> Vc<Box<dyn AssetContext>> {
-        Vc::upcast(ModuleAssetContext::new(
+    async fn emit_typed_routes(self: Vc<Self>) -> Result<Vc<Completion>> {
+        if !*self.next_config().typed_routes().await? {
+            return Ok(Completion::immutable());
+        }
+
+        let mut routes = vec![];
+        if let Some(app_project) = &*self.app_project().await? {
+            routes.extend(
+                app_typed_routes(app_project.app_entrypoints())
+                    .await?
+                    .iter()
+                    .cloned(),
+            );
+        }
+        routes.extend(
+            pages_typed_routes(self.pages_project().pages_structure())
+                .await?
+                .iter()
+                .cloned(),
+        );
+        routes.extend(
+            custom_typed_routes(self.next_config())
+                .await?
+                .iter()
+                .cloned(),
+        );
+
+        let node_root = self.node_root();
+        let declaration = build_typed_routes_declaration(node_root, Vc::cell(routes));
+        Ok(emit_assets(
+            Vc::cell(vec![declaration]),
+            node_root,
+            self.client_relative_path(),
+            node_root,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn middleware_context(self: Vc<Self>) -> Result<Vc<Box<dyn AssetContext>>> {
+        let mode = self.await?.mode;
+        Ok(Vc::upcast(ModuleAssetContext::new(
             Default::default(),
             self.edge_compile_time_info(),
             get_server_module_options_context(
                 self.project_path(),
                 self.execution_context(),
                 Value::new(ServerContextType::Middleware),
-                NextMode::Development,
+                mode,
                 self.next_config(),
             ),
             get_edge_resolve_options_context(
                 self.project_path(),
                 Value::new(ServerContextType::Middleware),
-                NextMode::Development,
+                mode,
                 self.next_config(),
                 self.execution_context(),
             ),
             Vc::cell("middleware".to_string()),
-        ))
+        )))
     }
 
     #[turbo_tasks::function]
@@ -730,26 +807,27 @@ impl Project {
     }
 
     #[turbo_tasks::function]
-    fn node_instrumentation_context(self: Vc<Self>) -> Vc<Box<dyn AssetContext>> {
-        Vc::upcast(ModuleAssetContext::new(
+    async fn node_instrumentation_context(self: Vc<Self>) -> Result<Vc<Box<dyn AssetContext>>> {
+        let mode = self.await?.mode;
+        Ok(Vc::upcast(ModuleAssetContext::new(
             Default::default(),
             self.server_compile_time_info(),
             get_server_module_options_context(
                 self.project_path(),
                 self.execution_context(),
                 Value::new(ServerContextType::Instrumentation),
-                NextMode::Development,
+                mode,
                 self.next_config(),
             ),
             get_server_resolve_options_context(
                 self.project_path(),
                 Value::new(ServerContextType::Instrumentation),
-                NextMode::Development,
+                mode,
                 self.next_config(),
                 self.execution_context(),
             ),
             Vc::cell("instrumentation".to_string()),
-        ))
+        )))
     }
 
     #[turbo_tasks::function]
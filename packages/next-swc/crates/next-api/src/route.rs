@@ -1,4 +1,5 @@
 use indexmap::IndexMap;
+use next_core::{LoaderTreeSegmentExports, NextSegmentConfig, NextSegmentExports};
 use turbo_tasks::{Completion, Vc};
 
 use crate::server_paths::ServerPath;
@@ -9,6 +10,8 @@ pub enum Route {
     Page {
         html_endpoint: Vc<Box<dyn Endpoint>>,
         data_endpoint: Vc<Box<dyn Endpoint>>,
+        /// The data fetching exports of the page.
+        exports: Vc<NextSegmentExports>,
     },
     PageApi {
         endpoint: Vc<Box<dyn Endpoint>>,
@@ -16,6 +19,11 @@ pub enum Route {
     AppPage {
         html_endpoint: Vc<Box<dyn Endpoint>>,
         rsc_endpoint: Vc<Box<dyn Endpoint>>,
+        /// The segment config of the page, merged from all of its segments.
+        config: Vc<NextSegmentConfig>,
+        /// The exports relevant for static generation of each segment of the
+        /// page.
+        segment_exports: Vc<LoaderTreeSegmentExports>,
     },
     AppRoute {
         endpoint: Vc<Box<dyn Endpoint>>,
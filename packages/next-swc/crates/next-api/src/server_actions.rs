@@ -3,6 +3,7 @@ use std::{collections::BTreeMap, io::Write, iter::once};
 use anyhow::{bail, Context, Result};
 use indexmap::{map::Entry, IndexMap};
 use next_core::{
+    export_issue_source,
     next_manifests::{ActionLayer, ActionManifestWorkerEntry, ServerReferenceManifest},
     util::{get_asset_prefix_from_pathname, NextRuntime},
 };
@@ -19,6 +20,8 @@ use turbopack_binding::{
             asset::{Asset, AssetContent},
             chunk::{ChunkItemExt, ChunkableModule, EvaluatableAsset},
             context::AssetContext,
+            file_source::FileSource,
+            issue::IssueExt,
             module::Module,
             output::OutputAsset,
             reference::primary_referenced_modules,
@@ -36,6 +39,8 @@ use turbopack_binding::{
     },
 };
 
+use crate::export::{ExportIssue, UnsupportedExportFeature};
+
 /// Scans the RSC entry point's full module graph looking for exported Server
 /// Actions (identifiable by a magic comment in the transformed module's
 /// output), and constructs a evaluatable "action loader" entry point and
@@ -70,6 +75,31 @@ pub(crate) async fn create_server_actions_manifest(
     Ok((evaluable, manifest))
 }
 
+/// Reports every module exporting a Server Action reachable from the RSC
+/// entry, as Server Actions can't be used with `output: 'export'`.
+pub(crate) async fn validate_server_actions_export(
+    rsc_entry: Vc<Box<dyn Module>>,
+    server_reference_modules: Vc<Vec<Vc<Box<dyn Module>>>>,
+    asset_context: Vc<Box<dyn AssetContext>>,
+) -> Result<()> {
+    let actions = get_actions(rsc_entry, server_reference_modules, asset_context).await?;
+    // One issue per module, pointing at its first action.
+    let mut modules = IndexMap::new();
+    for (_layer, name, module) in actions.values() {
+        modules.entry(*module).or_insert_with(|| name.clone());
+    }
+    for (module, name) in modules {
+        let path = module.ident().path();
+        ExportIssue::new(
+            path,
+            UnsupportedExportFeature::ServerActions,
+            export_issue_source(Vc::upcast(FileSource::new(path)), name),
+        )
+        .emit();
+    }
+    Ok(())
+}
+
 /// Builds the "action loader" entry point, which reexports every found action
 /// behind a lazy dynamic import.
 ///
@@ -13,7 +13,7 @@ pub struct BuildOptions {
     /// next.config.js's distDir.
     pub dist_dir: Option<String>,
 
-    /// The maximum memory to use for the build.
+    /// The maximum memory to use for the build, in megabytes.
     pub memory_limit: Option<usize>,
 
     /// The log level to use for the build.
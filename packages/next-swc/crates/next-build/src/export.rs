@@ -0,0 +1,68 @@
+//! The output of `output: 'export'`.
+//!
+//! Writes the `export-marker.json` Next.js checks before exporting, and lays
+//! out the static assets and the public directory in `out`. No HTML is
+//! rendered for any route, so `out` can't be served on its own: the pages
+//! still have to be exported by Next.js.
+//!
+//! Keep in sync with `packages/next/src/export/index.ts`.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use next_core::next_config::NextConfig;
+use serde_json::json;
+
+use crate::file_tracing::copy_file;
+
+const EXPORT_MARKER_VERSION: u32 = 1;
+
+pub(crate) fn write_export(
+    next_config: &NextConfig,
+    project_path: &Path,
+    dist_dir: &Path,
+    is_next_image_imported: bool,
+) -> Result<()> {
+    std::fs::write(
+        dist_dir.join("export-marker.json"),
+        serde_json::to_string(&json!({
+            "version": EXPORT_MARKER_VERSION,
+            "hasExportPathMap": false,
+            "exportTrailingSlash": next_config.trailing_slash.unwrap_or(false),
+            "isNextImageImported": is_next_image_imported,
+        }))?,
+    )
+    .context("unable to write export-marker.json")?;
+
+    let out_dir = project_path.join("out");
+    if out_dir.exists() {
+        std::fs::remove_dir_all(&out_dir).context("unable to remove the out directory")?;
+    }
+
+    copy_dir(
+        &dist_dir.join("static"),
+        &out_dir.join("_next").join("static"),
+    )?;
+    copy_dir(&project_path.join("public"), &out_dir)?;
+
+    Ok(())
+}
+
+/// Recursively copies the files in `from` into `to`. Does nothing if `from`
+/// doesn't exist.
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    if !from.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            copy_file(&path, &target)?;
+        }
+    }
+    Ok(())
+}
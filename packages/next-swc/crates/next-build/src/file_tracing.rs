@@ -0,0 +1,488 @@
+//! Output file tracing of the Node.js server entries.
+//!
+//! Writes a `.nft.json` file next to every server entry, listing the files it
+//! needs at runtime, and assembles `.next/standalone` for
+//! `output: 'standalone'`.
+//!
+//! Keep in sync with `packages/next/src/build/collect-build-traces.ts` and
+//! `copyTracedFiles` in `packages/next/src/build/utils.ts`.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use glob::{MatchOptions, Pattern};
+use indoc::formatdoc;
+use next_api::server_paths::ServerPath;
+use next_core::next_config::{NextConfig, OutputType};
+use serde_json::json;
+use turbo_tasks::TurboTasks;
+use turbopack_binding::{
+    features::node_file_trace::{start, Args},
+    turbo::tasks_memory::MemoryBackend,
+    turbopack::turbopack::{
+        module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+    },
+};
+
+const TRACE_OUTPUT_VERSION: u32 = 1;
+
+/// The entries of the Next.js server, relative to the `next` package.
+const NEXT_SERVER_ENTRIES: [&str; 4] = [
+    "dist/server/next-server.js",
+    "dist/server/next.js",
+    "dist/server/require-hook.js",
+    "dist/server/lib/start-server.js",
+];
+
+const GLOB_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A Node.js server entry written by the build.
+pub(crate) struct ServerEntry<'a> {
+    /// The pathname of the route the entry belongs to.
+    pub pathname: &'a str,
+    /// Relative to the dist dir.
+    pub entry_path: &'a str,
+    /// All server files of the entry.
+    pub server_paths: &'a [ServerPath],
+}
+
+pub(crate) struct FileTracing<'a> {
+    next_config: &'a NextConfig,
+    project_path: &'a Path,
+    dist_dir: &'a Path,
+    /// The directory files are traced relative to, `outputFileTracingRoot`.
+    root: PathBuf,
+    ignores: Vec<Pattern>,
+    /// The instance running the build, which the tracing runs on as well.
+    turbo_tasks: &'a Arc<TurboTasks<MemoryBackend>>,
+}
+
+impl<'a> FileTracing<'a> {
+    pub fn new(
+        next_config: &'a NextConfig,
+        root_path: &Path,
+        project_path: &'a Path,
+        dist_dir: &'a Path,
+        turbo_tasks: &'a Arc<TurboTasks<MemoryBackend>>,
+    ) -> Result<Self> {
+        let experimental = &next_config.experimental;
+        let root = match &experimental.output_file_tracing_root {
+            Some(root) => dunce::canonicalize(project_path.join(root))
+                .context("outputFileTracingRoot can't be found")?,
+            None => root_path.to_path_buf(),
+        };
+        let ignores = experimental
+            .output_file_tracing_ignores
+            .iter()
+            .flatten()
+            .map(|ignore| Pattern::new(ignore))
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid glob in outputFileTracingIgnores")?;
+
+        Ok(FileTracing {
+            next_config,
+            project_path,
+            dist_dir,
+            root,
+            ignores,
+            turbo_tasks,
+        })
+    }
+
+    /// Traces all server entries and the Next.js server, writing their
+    /// `.nft.json` files. For `output: 'standalone'`, the traced files are
+    /// then copied into `.next/standalone` together with `edge_paths`, the
+    /// server files of edge entries.
+    pub async fn run(&self, entries: &[ServerEntry<'_>], edge_paths: &[ServerPath]) -> Result<()> {
+        let mut all_files = BTreeSet::new();
+        let mut traced_entries = HashSet::new();
+        for entry in entries {
+            // Endpoints of the same page share their entry.
+            if !traced_entries.insert(entry.entry_path) {
+                continue;
+            }
+            let entry_path = self.dist_dir.join(entry.entry_path);
+            let mut files = self.trace(&[entry_path.clone()]).await?;
+            files.extend(
+                entry
+                    .server_paths
+                    .iter()
+                    .map(|server_path| self.dist_dir.join(&server_path.path)),
+            );
+            files.extend(self.included_files(entry.pathname)?);
+            files.remove(&entry_path);
+            files.retain(|file| !self.is_ignored(file));
+            write_trace(&entry_path, &files)?;
+            all_files.extend(files);
+            all_files.insert(entry_path);
+        }
+
+        let next_package = find_next_package(self.project_path)?;
+        let mut server_files = self
+            .trace(&NEXT_SERVER_ENTRIES.map(|entry| next_package.join(entry)))
+            .await?;
+        server_files.retain(|file| !self.is_ignored(file));
+        write_trace(&self.dist_dir.join("next-server.js"), &server_files)?;
+        all_files.extend(server_files);
+
+        if self.next_config.output == Some(OutputType::Standalone) {
+            all_files.extend(
+                edge_paths
+                    .iter()
+                    .map(|server_path| self.dist_dir.join(&server_path.path)),
+            );
+            self.write_standalone(&all_files)?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs node-file-trace over `input`, returning the absolute paths of all
+    /// files the input depends on, including the input itself.
+    async fn trace(&self, input: &[PathBuf]) -> Result<BTreeSet<PathBuf>> {
+        let args: Args = serde_json::from_value(json!({
+            "action": "print",
+            "input": input,
+            "contextDirectory": self.root,
+            "processCwd": self.project_path,
+        }))?;
+        let files = start(
+            Arc::new(args),
+            Some(self.turbo_tasks),
+            Some(ModuleOptionsContext {
+                enable_types: true,
+                ..Default::default()
+            }),
+            Some(ResolveOptionsContext::default()),
+        )
+        .await?;
+
+        Ok(files.into_iter().map(|file| self.root.join(file)).collect())
+    }
+
+    /// The files added to a route by `outputFileTracingIncludes`.
+    fn included_files(&self, pathname: &str) -> Result<Vec<PathBuf>> {
+        let mut files = vec![];
+        let includes = &self.next_config.experimental.output_file_tracing_includes;
+        for (route, globs) in includes.iter().flatten() {
+            let route = Pattern::new(route).with_context(|| {
+                format!("invalid route glob {route} in outputFileTracingIncludes")
+            })?;
+            if !route.matches_with(pathname, GLOB_OPTIONS) {
+                continue;
+            }
+            for include in globs {
+                let include = self.project_path.join(include);
+                let include = include.to_str().with_context(|| {
+                    format!("{} contains invalid characters", include.display())
+                })?;
+                for file in glob::glob_with(include, GLOB_OPTIONS).with_context(|| {
+                    format!("invalid glob {include} in outputFileTracingIncludes")
+                })? {
+                    let file = file?;
+                    if file.is_file() {
+                        files.push(file);
+                    }
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// Whether `file` matches any of `outputFileTracingIgnores`. Like
+    /// Next.js, ignores may match any trailing part of the path, and files
+    /// outside of the tracing root are always ignored.
+    fn is_ignored(&self, file: &Path) -> bool {
+        let Ok(relative) = file.strip_prefix(&self.root) else {
+            return true;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let mut suffixes = std::iter::once(0)
+            .chain(relative.match_indices('/').map(|(index, _)| index + 1))
+            .map(|start| &relative[start..]);
+        suffixes.any(|suffix| {
+            self.ignores
+                .iter()
+                .any(|ignore| ignore.matches_with(suffix, GLOB_OPTIONS))
+        })
+    }
+
+    /// Copies `files` into `.next/standalone`, together with the merged
+    /// manifests and a `server.js` to start the server with.
+    fn write_standalone(&self, files: &BTreeSet<PathBuf>) -> Result<()> {
+        let standalone_dir = self.dist_dir.join("standalone");
+        if standalone_dir.exists() {
+            std::fs::remove_dir_all(&standalone_dir)
+                .context("unable to remove the standalone directory")?;
+        }
+
+        for file in files {
+            if let Ok(relative) = file.strip_prefix(&self.root) {
+                copy_file(file, &standalone_dir.join(relative))?;
+            }
+        }
+
+        // The merged manifests are read by the server directly instead of being
+        // referenced by any entry.
+        for dir in [self.dist_dir.to_path_buf(), self.dist_dir.join("server")] {
+            for entry in std::fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_file() {
+                    copy_file(&path, &self.standalone_path(&standalone_dir, &path)?)?;
+                }
+            }
+        }
+
+        let package_json = self.project_path.join("package.json");
+        if package_json.is_file() {
+            copy_file(
+                &package_json,
+                &self.standalone_path(&standalone_dir, &package_json)?,
+            )?;
+        }
+
+        let server_js = self.project_path.join("server.js");
+        let mut next_config = serde_json::to_value(self.next_config)?;
+        next_config["distDir"] = json!(format!(
+            "./{}",
+            self.dist_dir
+                .strip_prefix(self.project_path)
+                .context("the dist dir must be inside the project directory")?
+                .to_string_lossy()
+                .replace('\\', "/")
+        ));
+        std::fs::write(
+            self.standalone_path(&standalone_dir, &server_js)?,
+            standalone_server(&serde_json::to_string(&next_config)?),
+        )
+        .context("unable to write the standalone server.js")?;
+
+        Ok(())
+    }
+
+    fn standalone_path(&self, standalone_dir: &Path, path: &Path) -> Result<PathBuf> {
+        Ok(
+            standalone_dir.join(path.strip_prefix(&self.root).with_context(|| {
+                format!(
+                    "{} must be inside outputFileTracingRoot {}",
+                    path.display(),
+                    self.root.display()
+                )
+            })?),
+        )
+    }
+}
+
+/// Writes the `.nft.json` file of `entry_path`, listing `files` relative to
+/// the directory of the entry.
+fn write_trace(entry_path: &Path, files: &BTreeSet<PathBuf>) -> Result<()> {
+    let entry_dir = entry_path
+        .parent()
+        .context("server entries must be inside the dist dir")?;
+    let files = files
+        .iter()
+        .filter_map(|file| pathdiff::diff_paths(file, entry_dir))
+        .map(|file| file.to_string_lossy().replace('\\', "/"))
+        .collect::<Vec<_>>();
+
+    let mut trace_path = entry_path.as_os_str().to_owned();
+    trace_path.push(".nft.json");
+    std::fs::write(
+        &trace_path,
+        serde_json::to_string(&json!({
+            "version": TRACE_OUTPUT_VERSION,
+            "files": files,
+        }))?,
+    )
+    .with_context(|| format!("unable to write {}", Path::new(&trace_path).display()))
+}
+
+fn find_next_package(project_path: &Path) -> Result<PathBuf> {
+    project_path
+        .ancestors()
+        .map(|dir| dir.join("node_modules").join("next"))
+        .find(|next_package| next_package.join("package.json").is_file())
+        .context("unable to find the next package to trace the server")
+}
+
+pub(crate) fn copy_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(dir) = to.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("unable to create {}", dir.display()))?;
+    }
+    std::fs::copy(from, to)
+        .with_context(|| format!("unable to copy {} to {}", from.display(), to.display()))?;
+    Ok(())
+}
+
+fn standalone_server(next_config: &str) -> String {
+    formatdoc! {
+        r#"
+            const path = require('path')
+
+            const dir = path.join(__dirname)
+
+            process.env.NODE_ENV = 'production'
+            process.chdir(__dirname)
+
+            const currentPort = parseInt(process.env.PORT, 10) || 3000
+            const hostname = process.env.HOSTNAME || '0.0.0.0'
+
+            let keepAliveTimeout = parseInt(process.env.KEEP_ALIVE_TIMEOUT, 10)
+            const nextConfig = {next_config}
+
+            process.env.__NEXT_PRIVATE_STANDALONE_CONFIG = JSON.stringify(nextConfig)
+
+            require('next')
+            const {{ startServer }} = require('next/dist/server/lib/start-server')
+
+            if (
+              Number.isNaN(keepAliveTimeout) ||
+              !Number.isFinite(keepAliveTimeout) ||
+              keepAliveTimeout < 0
+            ) {{
+              keepAliveTimeout = undefined
+            }}
+
+            startServer({{
+              dir,
+              isDev: false,
+              config: nextConfig,
+              hostname,
+              port: currentPort,
+              allowRetry: false,
+              keepAliveTimeout,
+            }}).catch((err) => {{
+              console.error(err);
+              process.exit(1);
+            }});
+        "#
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, path::Path, sync::Arc};
+
+    use next_core::next_config::{ExperimentalConfig, NextConfig, OutputType};
+    use turbo_tasks::TurboTasks;
+    use turbopack_binding::turbo::tasks_memory::MemoryBackend;
+
+    use super::FileTracing;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ignores_files_by_any_trailing_path() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let project = root.join("app");
+        let dist_dir = project.join(".next");
+        let next_config = NextConfig {
+            experimental: ExperimentalConfig {
+                output_file_tracing_ignores: Some(vec![
+                    "node_modules/sharp/**".to_string(),
+                    "*.md".to_string(),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        let tracing = FileTracing::new(&next_config, root, &project, &dist_dir, &tt).unwrap();
+
+        assert!(tracing.is_ignored(&root.join("node_modules/sharp/lib/index.js")));
+        assert!(tracing.is_ignored(&project.join("node_modules/sharp/lib/index.js")));
+        assert!(tracing.is_ignored(&project.join("docs/README.md")));
+        assert!(!tracing.is_ignored(&project.join("node_modules/react/index.js")));
+        assert!(!tracing.is_ignored(&dist_dir.join("server/pages/index.js")));
+        // Outside of the tracing root.
+        assert!(tracing.is_ignored(&root.parent().unwrap().join("index.js")));
+    }
+
+    #[tokio::test]
+    async fn includes_files_by_route() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let project = root.join("app");
+        let dist_dir = project.join(".next");
+        write(&project.join("data/a.json"), "{}");
+        write(&project.join("data/nested/b.json"), "{}");
+        write(&project.join("data/c.txt"), "");
+        let next_config = NextConfig {
+            experimental: ExperimentalConfig {
+                output_file_tracing_includes: Some(
+                    [("/api/*".to_string(), vec!["data/**/*.json".to_string()])]
+                        .into_iter()
+                        .collect(),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        let tracing = FileTracing::new(&next_config, root, &project, &dist_dir, &tt).unwrap();
+
+        assert_eq!(
+            tracing.included_files("/api/hello").unwrap(),
+            [
+                project.join("data/a.json"),
+                project.join("data/nested/b.json")
+            ]
+        );
+        assert!(tracing
+            .included_files("/api/hello/world")
+            .unwrap()
+            .is_empty());
+        assert!(tracing.included_files("/blog").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn copies_traced_files_into_standalone() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let project = root.join("app");
+        let dist_dir = project.join(".next");
+        let page = dist_dir.join("server/pages/index.js");
+        let dependency = root.join("node_modules/react/index.js");
+        write(&page, "page");
+        write(&dependency, "react");
+        write(&dist_dir.join("routes-manifest.json"), "{}");
+        write(&dist_dir.join("server/pages-manifest.json"), "{}");
+        write(&project.join("package.json"), "{}");
+        // Left over from a previous build.
+        write(&dist_dir.join("standalone/stale.js"), "");
+        let next_config = NextConfig {
+            output: Some(OutputType::Standalone),
+            ..Default::default()
+        };
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        let tracing = FileTracing::new(&next_config, root, &project, &dist_dir, &tt).unwrap();
+
+        tracing
+            .write_standalone(&BTreeSet::from([page, dependency]))
+            .unwrap();
+
+        let standalone = dist_dir.join("standalone");
+        let read = |path: &str| std::fs::read_to_string(standalone.join(path)).unwrap();
+        assert_eq!(read("app/.next/server/pages/index.js"), "page");
+        assert_eq!(read("node_modules/react/index.js"), "react");
+        assert_eq!(read("app/.next/routes-manifest.json"), "{}");
+        assert_eq!(read("app/.next/server/pages-manifest.json"), "{}");
+        assert_eq!(read("app/package.json"), "{}");
+        assert!(read("app/server.js").contains(r#""distDir":"./.next""#));
+        assert!(!standalone.join("stale.js").exists());
+        assert!(!standalone.join("app/.next/standalone").exists());
+    }
+}
@@ -2,10 +2,14 @@
 #![feature(arbitrary_self_types)]
 
 pub mod build_options;
+mod export;
+mod file_tracing;
+mod manifests;
 pub(crate) mod next_app;
+pub(crate) mod next_build;
 pub(crate) mod next_pages;
 
-pub use self::build_options::BuildOptions;
+pub use self::{build_options::BuildOptions, next_build::build};
 
 pub fn register() {
     turbopack_binding::turbo::tasks::register();
@@ -16,5 +20,6 @@ pub fn register() {
     turbopack_binding::turbopack::dev::register();
     turbopack_binding::turbopack::build::register();
     next_core::register();
+    next_api::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use next_build::build_options::{BuildOptions, DefineEnv};
+use turbopack_binding::turbopack::core::{error::PrettyPrintError, issue::IssueSeverity};
+
+#[derive(Debug, Parser)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    /// The project's directory. Defaults to the current directory.
+    #[clap(value_parser)]
+    dir: Option<PathBuf>,
+
+    /// The root directory of the workspace. Defaults to the project's
+    /// directory.
+    #[clap(long, value_parser)]
+    root: Option<PathBuf>,
+
+    /// Overrides next.config.js's distDir.
+    #[clap(long, value_parser)]
+    dist_dir: Option<String>,
+
+    /// The maximum memory to use for the build, in megabytes.
+    #[clap(long, value_parser)]
+    memory_limit: Option<usize>,
+
+    /// The minimum severity of issues to display. The build fails on issues
+    /// at or above this severity, or on errors when unset.
+    #[clap(long, value_parser = parse_issue_severity)]
+    log_level: Option<IssueSeverity>,
+
+    /// Show all issues, including those in node_modules.
+    #[clap(long)]
+    show_all: bool,
+
+    /// Show the details of each issue.
+    #[clap(long)]
+    log_detail: bool,
+
+    /// Compute full stats instead of essential stats only.
+    #[clap(long)]
+    full_stats: bool,
+
+    /// A `KEY=VALUE` pair to inject at compile time into client, edge and
+    /// Node.js code. Can be repeated.
+    #[clap(long = "define-env", value_parser = parse_define)]
+    define_env: Vec<(String, String)>,
+
+    /// A `KEY=VALUE` pair to inject at compile time into client code only.
+    #[clap(long = "define-env-client", value_parser = parse_define)]
+    define_env_client: Vec<(String, String)>,
+
+    /// A `KEY=VALUE` pair to inject at compile time into edge code only.
+    #[clap(long = "define-env-edge", value_parser = parse_define)]
+    define_env_edge: Vec<(String, String)>,
+
+    /// A `KEY=VALUE` pair to inject at compile time into Node.js code only.
+    #[clap(long = "define-env-nodejs", value_parser = parse_define)]
+    define_env_nodejs: Vec<(String, String)>,
+}
+
+impl From<Cli> for BuildOptions {
+    fn from(cli: Cli) -> Self {
+        let with_shared = |specific: Vec<(String, String)>| {
+            cli.define_env.iter().cloned().chain(specific).collect()
+        };
+        let define_env = DefineEnv {
+            client: with_shared(cli.define_env_client),
+            edge: with_shared(cli.define_env_edge),
+            nodejs: with_shared(cli.define_env_nodejs),
+        };
+
+        BuildOptions {
+            root: cli.root,
+            dir: cli.dir,
+            dist_dir: cli.dist_dir,
+            memory_limit: cli.memory_limit,
+            log_level: cli.log_level,
+            show_all: cli.show_all,
+            log_detail: cli.log_detail,
+            full_stats: cli.full_stats,
+            build_context: None,
+            define_env,
+        }
+    }
+}
+
+fn parse_issue_severity(value: &str) -> Result<IssueSeverity> {
+    Ok(match value.to_lowercase().as_str() {
+        "bug" => IssueSeverity::Bug,
+        "fatal" => IssueSeverity::Fatal,
+        "error" => IssueSeverity::Error,
+        "warning" => IssueSeverity::Warning,
+        "hint" => IssueSeverity::Hint,
+        "note" => IssueSeverity::Note,
+        "suggestion" => IssueSeverity::Suggestion,
+        "info" => IssueSeverity::Info,
+        _ => {
+            return Err(anyhow!(
+                "expected one of bug, fatal, error, warning, hint, note, suggestion or info"
+            ))
+        }
+    })
+}
+
+fn parse_define(value: &str) -> Result<(String, String)> {
+    let (key, value) = value
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected KEY=VALUE, got `{value}`"))?;
+    Ok((key.to_string(), value.to_string()))
+}
+
+#[tokio::main]
+async fn main() {
+    let options = BuildOptions::from(Cli::parse());
+
+    if let Err(e) = next_build::build(options).await {
+        eprintln!("{}", PrettyPrintError(&e));
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,430 @@
+//! Merging of the partial, per-endpoint manifests emitted by the project into
+//! the top-level manifests read by the Next.js server.
+//!
+//! Keep in sync with `packages/next/src/server/dev/turbopack-utils.ts`.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use next_api::{route::WrittenEndpoint, server_paths::ServerPath};
+use next_core::{
+    next_config::{Rewrites, RouteHas},
+    next_manifests::ClientBuildManifest,
+};
+use serde_json::{json, Map, Value as JsonValue};
+
+const BUILD_MANIFEST: &str = "build-manifest.json";
+const APP_BUILD_MANIFEST: &str = "app-build-manifest.json";
+const PAGES_MANIFEST: &str = "pages-manifest.json";
+const APP_PATHS_MANIFEST: &str = "app-paths-manifest.json";
+const MIDDLEWARE_MANIFEST: &str = "middleware-manifest.json";
+const SERVER_REFERENCE_MANIFEST: &str = "server-reference-manifest";
+const NEXT_FONT_MANIFEST: &str = "next-font-manifest";
+const REACT_LOADABLE_MANIFEST: &str = "react-loadable-manifest.json";
+const FUNCTIONS_CONFIG_MANIFEST: &str = "functions-config-manifest.json";
+const SUBRESOURCE_INTEGRITY_MANIFEST: &str = "subresource-integrity-manifest";
+
+/// The `Next-Url` header used by interception route rewrites.
+const NEXT_URL: &str = "Next-Url";
+
+/// Partial manifests collected from written endpoints, keyed by their path
+/// relative to the dist dir so that endpoints sharing an output (e.g. the HTML
+/// and RSC endpoints of an app page) are only merged once.
+#[derive(Default)]
+pub(crate) struct PartialManifests {
+    build: BTreeMap<String, JsonValue>,
+    app_build: BTreeMap<String, JsonValue>,
+    pages: BTreeMap<String, JsonValue>,
+    app_paths: BTreeMap<String, JsonValue>,
+    middleware: BTreeMap<String, JsonValue>,
+    server_reference: BTreeMap<String, JsonValue>,
+    font: BTreeMap<String, JsonValue>,
+    loadable: BTreeMap<String, JsonValue>,
+    functions_config: BTreeMap<String, JsonValue>,
+    subresource_integrity: BTreeMap<String, JsonValue>,
+}
+
+impl PartialManifests {
+    /// Reads all partial manifests referenced by the server paths of a written
+    /// endpoint.
+    pub fn collect(&mut self, dist_dir: &Path, written: &WrittenEndpoint) -> Result<()> {
+        let server_paths = match written {
+            WrittenEndpoint::NodeJs { server_paths, .. } => server_paths,
+            WrittenEndpoint::Edge { server_paths } => server_paths,
+        };
+
+        for ServerPath { path, .. } in server_paths {
+            // Partial manifests are always nested below `server/{pages,app,...}/`,
+            // unlike the merged manifests which live at the top of `server/`.
+            if path.matches('/').count() < 2 {
+                continue;
+            }
+            let Some((_, file_name)) = path.rsplit_once('/') else {
+                continue;
+            };
+            let manifests = match file_name {
+                BUILD_MANIFEST => &mut self.build,
+                APP_BUILD_MANIFEST => &mut self.app_build,
+                PAGES_MANIFEST => &mut self.pages,
+                APP_PATHS_MANIFEST => &mut self.app_paths,
+                MIDDLEWARE_MANIFEST => &mut self.middleware,
+                REACT_LOADABLE_MANIFEST => &mut self.loadable,
+                FUNCTIONS_CONFIG_MANIFEST => &mut self.functions_config,
+                _ if file_name == format!("{SERVER_REFERENCE_MANIFEST}.json") => {
+                    &mut self.server_reference
+                }
+                _ if file_name == format!("{NEXT_FONT_MANIFEST}.json") => &mut self.font,
+                _ if file_name == format!("{SUBRESOURCE_INTEGRITY_MANIFEST}.json") => {
+                    &mut self.subresource_integrity
+                }
+                _ => continue,
+            };
+            if manifests.contains_key(path) {
+                continue;
+            }
+            let content = std::fs::read_to_string(dist_dir.join(path))
+                .with_context(|| format!("unable to read partial manifest {path}"))?;
+            let manifest = serde_json::from_str(&content)
+                .with_context(|| format!("unable to parse partial manifest {path}"))?;
+            manifests.insert(path.clone(), manifest);
+        }
+
+        Ok(())
+    }
+
+    /// Writes the merged manifests into the dist dir.
+    pub fn write(
+        &self,
+        dist_dir: &Path,
+        build_id: &str,
+        rewrites: &Rewrites,
+        sorted_pages: &[String],
+    ) -> Result<()> {
+        let server_dir = dist_dir.join("server");
+
+        let build_manifest = merge_build_manifests(self.build.values(), build_id);
+        write_json(&dist_dir.join(BUILD_MANIFEST), &build_manifest)?;
+        write_file(
+            &server_dir.join("middleware-build-manifest.js"),
+            format!(
+                "self.__BUILD_MANIFEST={};",
+                serde_json::to_string(&build_manifest)?
+            ),
+        )?;
+
+        let fallback_build_manifest = merge_build_manifests(
+            self.build
+                .iter()
+                .filter(|(path, _)| {
+                    path.as_str() == "server/pages/_app/build-manifest.json"
+                        || path.as_str() == "server/pages/_error/build-manifest.json"
+                })
+                .map(|(_, manifest)| manifest),
+            build_id,
+        );
+        write_json(
+            &dist_dir.join(format!("fallback-{BUILD_MANIFEST}")),
+            &fallback_build_manifest,
+        )?;
+
+        let interception_rewrites = rewrites
+            .before_files
+            .iter()
+            .filter(|rewrite| {
+                matches!(
+                    rewrite.has.as_deref(),
+                    Some([RouteHas::Header { key, .. }, ..]) if key == NEXT_URL
+                )
+            })
+            .collect::<Vec<_>>();
+        write_file(
+            &server_dir.join("interception-route-rewrite-manifest.js"),
+            format!(
+                "self.__INTERCEPTION_ROUTE_REWRITE_MANIFEST={};",
+                serde_json::to_string(&serde_json::to_string(&interception_rewrites)?)?
+            ),
+        )?;
+
+        let client_pages = build_manifest["pages"]
+            .as_object()
+            .map(|pages| {
+                pages
+                    .iter()
+                    .map(|(page, files)| {
+                        let files = files
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|file| file.as_str())
+                            .collect();
+                        (page.clone(), files)
+                    })
+                    .collect::<HashMap<_, _>>()
+            })
+            .unwrap_or_default();
+        let client_build_manifest = ClientBuildManifest {
+            rewrites,
+            sorted_pages,
+            pages: client_pages,
+        };
+        let static_dir = dist_dir.join("static").join(build_id);
+        write_file(
+            &static_dir.join("_buildManifest.js"),
+            format!(
+                "self.__BUILD_MANIFEST = {};self.__BUILD_MANIFEST_CB && self.__BUILD_MANIFEST_CB()",
+                serde_json::to_string(&client_build_manifest)?
+            ),
+        )?;
+        write_file(
+            &static_dir.join("_ssgManifest.js"),
+            "self.__SSG_MANIFEST=new Set;self.__SSG_MANIFEST_CB&&self.__SSG_MANIFEST_CB()"
+                .to_string(),
+        )?;
+
+        let mut app_build_manifest = json!({ "pages": {}, "integrity": {} });
+        for manifest in self.app_build.values() {
+            assign(&mut app_build_manifest["pages"], &manifest["pages"]);
+            assign(&mut app_build_manifest["integrity"], &manifest["integrity"]);
+        }
+        remove_if_empty(&mut app_build_manifest, "integrity");
+        write_json(&dist_dir.join(APP_BUILD_MANIFEST), &app_build_manifest)?;
+
+        write_json(
+            &server_dir.join(PAGES_MANIFEST),
+            &merge_objects(self.pages.values()),
+        )?;
+        write_json(
+            &server_dir.join(APP_PATHS_MANIFEST),
+            &merge_objects(self.app_paths.values()),
+        )?;
+        write_json(
+            &server_dir.join(MIDDLEWARE_MANIFEST),
+            &merge_middleware_manifests(self.middleware.values()),
+        )?;
+
+        let mut functions_config_manifest = json!({ "version": 1, "functions": {} });
+        for manifest in self.functions_config.values() {
+            assign(
+                &mut functions_config_manifest["functions"],
+                &manifest["functions"],
+            );
+        }
+        write_json(
+            &server_dir.join(FUNCTIONS_CONFIG_MANIFEST),
+            &functions_config_manifest,
+        )?;
+
+        let server_reference_manifest =
+            merge_server_reference_manifests(self.server_reference.values());
+        write_json_and_js(
+            &server_dir,
+            SERVER_REFERENCE_MANIFEST,
+            "__RSC_SERVER_MANIFEST",
+            &server_reference_manifest,
+        )?;
+
+        let font_manifest = merge_font_manifests(self.font.values());
+        write_json_and_js(
+            &server_dir,
+            NEXT_FONT_MANIFEST,
+            "__NEXT_FONT_MANIFEST",
+            &font_manifest,
+        )?;
+
+        // Only written when `experimental.sri` is enabled, like in Next.js.
+        if !self.subresource_integrity.is_empty() {
+            write_json_and_js(
+                &server_dir,
+                SUBRESOURCE_INTEGRITY_MANIFEST,
+                "__SUBRESOURCE_INTEGRITY_MANIFEST",
+                &merge_objects(self.subresource_integrity.values()),
+            )?;
+        }
+
+        let loadable_manifest = merge_objects(self.loadable.values());
+        write_json(&dist_dir.join(REACT_LOADABLE_MANIFEST), &loadable_manifest)?;
+        write_file(
+            &server_dir.join("middleware-react-loadable-manifest.js"),
+            format!(
+                "self.__REACT_LOADABLE_MANIFEST={}",
+                serde_json::to_string(&serde_json::to_string_pretty(&loadable_manifest)?)?
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Equivalent of `Object.assign(target, source)` for JSON objects.
+fn assign(target: &mut JsonValue, source: &JsonValue) {
+    if let (Some(target), Some(source)) = (target.as_object_mut(), source.as_object()) {
+        for (key, value) in source {
+            target.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// Removes `key` from a JSON object if it's an empty object.
+fn remove_if_empty(target: &mut JsonValue, key: &str) {
+    if let Some(target) = target.as_object_mut() {
+        if matches!(target.get(key), Some(JsonValue::Object(value)) if value.is_empty()) {
+            target.remove(key);
+        }
+    }
+}
+
+fn merge_objects<'a>(manifests: impl Iterator<Item = &'a JsonValue>) -> JsonValue {
+    let mut merged = JsonValue::Object(Map::new());
+    for manifest in manifests {
+        assign(&mut merged, manifest);
+    }
+    merged
+}
+
+fn merge_build_manifests<'a>(
+    manifests: impl Iterator<Item = &'a JsonValue>,
+    build_id: &str,
+) -> JsonValue {
+    let mut merged = json!({
+        "pages": {
+            "/_app": [],
+        },
+        "devFiles": [],
+        "ampDevFiles": [],
+        "polyfillFiles": [],
+        "lowPriorityFiles": [
+            format!("static/{build_id}/_ssgManifest.js"),
+            format!("static/{build_id}/_buildManifest.js"),
+        ],
+        "rootMainFiles": [],
+        "ampFirstPages": [],
+        "integrity": {},
+    });
+    for manifest in manifests {
+        assign(&mut merged["pages"], &manifest["pages"]);
+        assign(&mut merged["integrity"], &manifest["integrity"]);
+        if matches!(manifest["rootMainFiles"].as_array(), Some(files) if !files.is_empty()) {
+            merged["rootMainFiles"] = manifest["rootMainFiles"].clone();
+        }
+    }
+    remove_if_empty(&mut merged, "integrity");
+    merged
+}
+
+fn merge_middleware_manifests<'a>(manifests: impl Iterator<Item = &'a JsonValue>) -> JsonValue {
+    let mut merged = json!({
+        "version": 2,
+        "middleware": {},
+        "sortedMiddleware": [],
+        "functions": {},
+    });
+    let mut instrumentation_files = vec![];
+    for manifest in manifests {
+        assign(&mut merged["functions"], &manifest["functions"]);
+        assign(&mut merged["middleware"], &manifest["middleware"]);
+        if let Some(files) = manifest["instrumentation"]["files"].as_array() {
+            instrumentation_files = files.clone();
+        }
+    }
+
+    for key in ["middleware", "functions"] {
+        if let Some(definitions) = merged[key].as_object_mut() {
+            for definition in definitions.values_mut() {
+                let files = definition["files"].as_array().cloned().unwrap_or_default();
+                definition["files"] =
+                    JsonValue::Array(instrumentation_files.iter().cloned().chain(files).collect());
+            }
+        }
+    }
+
+    let sorted_middleware = merged["middleware"]
+        .as_object()
+        .map(|middleware| {
+            middleware
+                .keys()
+                .cloned()
+                .map(JsonValue::String)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    merged["sortedMiddleware"] = JsonValue::Array(sorted_middleware);
+    merged
+}
+
+fn merge_server_reference_manifests<'a>(
+    manifests: impl Iterator<Item = &'a JsonValue>,
+) -> JsonValue {
+    let mut merged = json!({
+        "node": {},
+        "edge": {},
+        "encryptionKey": STANDARD.encode(rand::random::<[u8; 32]>()),
+    });
+    for manifest in manifests {
+        for runtime in ["node", "edge"] {
+            let Some(actions) = manifest[runtime].as_object() else {
+                continue;
+            };
+            for (id, action) in actions {
+                let entry = merged[runtime]
+                    .as_object_mut()
+                    .expect("runtime entry is an object")
+                    .entry(id.clone())
+                    .or_insert_with(|| json!({ "workers": {}, "layer": {} }));
+                assign(&mut entry["workers"], &action["workers"]);
+                assign(&mut entry["layer"], &action["layer"]);
+            }
+        }
+    }
+    merged
+}
+
+fn merge_font_manifests<'a>(manifests: impl Iterator<Item = &'a JsonValue>) -> JsonValue {
+    let mut merged = json!({
+        "app": {},
+        "appUsingSizeAdjust": false,
+        "pages": {},
+        "pagesUsingSizeAdjust": false,
+    });
+    for manifest in manifests {
+        assign(&mut merged["app"], &manifest["app"]);
+        assign(&mut merged["pages"], &manifest["pages"]);
+        for key in ["appUsingSizeAdjust", "pagesUsingSizeAdjust"] {
+            let using_size_adjust =
+                merged[key].as_bool().unwrap_or(false) || manifest[key].as_bool().unwrap_or(false);
+            merged[key] = JsonValue::Bool(using_size_adjust);
+        }
+    }
+    merged
+}
+
+fn write_file(path: &Path, content: String) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("unable to create directory {}", parent.display()))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("unable to write {}", path.display()))
+}
+
+fn write_json(path: &Path, manifest: &JsonValue) -> Result<()> {
+    write_file(path, serde_json::to_string_pretty(manifest)?)
+}
+
+/// Writes a manifest as JSON, along with a JS file exposing it as a global for
+/// the edge runtime.
+fn write_json_and_js(
+    server_dir: &Path,
+    name: &str,
+    global: &str,
+    manifest: &JsonValue,
+) -> Result<()> {
+    let json = serde_json::to_string_pretty(manifest)?;
+    write_file(
+        &server_dir.join(format!("{name}.js")),
+        format!("self.{global}={}", serde_json::to_string(&json)?),
+    )?;
+    write_file(&server_dir.join(format!("{name}.json")), json)
+}
@@ -192,6 +192,7 @@ pub async fn get_app_entries(
                     page.clone(),
                     project_root,
                     next_config,
+                    mode,
                 ),
                 Entrypoint::AppRoute { page, path } => get_app_route_entry(
                     rsc_context,
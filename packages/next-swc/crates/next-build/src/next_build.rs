@@ -0,0 +1,436 @@
+use std::{
+    env::current_dir,
+    path::{Path, MAIN_SEPARATOR},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use next_api::{
+    prerender_manifest::build_prerender_manifest,
+    project::{DefineEnv as ProjectDefineEnv, ProjectContainer, ProjectOptions},
+    route::{Endpoint, Route, WrittenEndpoint},
+};
+use next_core::{
+    emit_assets,
+    next_config::{load_headers, load_next_config, load_redirects, load_rewrites, OutputType},
+    next_manifests::{build_images_manifest, build_routes_manifest, PreviewProps},
+};
+use turbo_tasks::{StatsType, TransientInstance, TryJoinIterExt, TurboTasks, Vc};
+use turbopack_binding::{
+    turbo::{
+        tasks_env::CommandLineProcessEnv, tasks_fs::DiskFileSystem, tasks_memory::MemoryBackend,
+    },
+    turbopack::{
+        cli_utils::issue::{ConsoleUi, LogOptions},
+        core::{
+            diagnostics::{Diagnostic, DiagnosticContextExt},
+            issue::{handle_issues, IssueReporter, IssueSeverity},
+            PROJECT_FILESYSTEM_NAME,
+        },
+        dev::DevChunkingContext,
+        node::execution_context::ExecutionContext,
+        turbopack::evaluate_context::node_build_environment,
+    },
+};
+
+use crate::{
+    build_options::{BuildOptions, DefineEnv},
+    export::write_export,
+    file_tracing::{FileTracing, ServerEntry},
+    manifests::PartialManifests,
+};
+
+/// Runs a production build of the project and writes the complete dist dir
+/// for both the pages and the app router.
+pub async fn build(options: BuildOptions) -> Result<()> {
+    #[cfg(feature = "tokio_console")]
+    console_subscriber::init();
+    crate::register();
+
+    let tt = TurboTasks::new(MemoryBackend::new(
+        options.memory_limit.map_or(usize::MAX, |l| l * 1024 * 1024),
+    ));
+
+    tt.set_stats_type(if options.full_stats {
+        StatsType::Full
+    } else {
+        StatsType::Essential
+    });
+
+    tt.run_once(next_build(options, tt.clone())).await
+}
+
+async fn next_build(
+    options: BuildOptions,
+    turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+) -> Result<()> {
+    let project_path = options
+        .dir
+        .as_ref()
+        .map_or_else(current_dir, |dir| Ok(dir.clone()))
+        .and_then(dunce::canonicalize)
+        .context("project directory can't be found")?;
+    let root_path = options
+        .root
+        .as_ref()
+        .map(dunce::canonicalize)
+        .transpose()
+        .context("root directory can't be found")?
+        .unwrap_or_else(|| project_path.clone());
+    if !project_path.starts_with(&root_path) {
+        bail!(
+            "project directory {} must be inside the root directory {}",
+            project_path.display(),
+            root_path.display()
+        );
+    }
+    let project_path_str = path_to_string(&project_path)?;
+    let root_path_str = path_to_string(&root_path)?;
+
+    let log_options = LogOptions {
+        current_dir: current_dir().unwrap_or_else(|_| project_path.clone()),
+        project_dir: project_path.clone(),
+        show_all: options.show_all,
+        log_detail: options.log_detail,
+        log_level: options.log_level.unwrap_or(IssueSeverity::Warning),
+    };
+    let issue_reporter: Vc<Box<dyn IssueReporter>> =
+        Vc::upcast(ConsoleUi::new(TransientInstance::new(log_options)));
+    // The build fails on any issue at or above the requested log level, and on
+    // errors when no log level was requested.
+    let min_failing_severity = options.log_level.unwrap_or(IssueSeverity::Error).cell();
+
+    let execution_context = project_execution_context(
+        root_path_str.clone(),
+        project_path_str.clone(),
+        options
+            .dist_dir
+            .clone()
+            .unwrap_or_else(|| ".next".to_string()),
+    );
+
+    let next_config = load_next_config(execution_context);
+    handle_issues(
+        next_config,
+        issue_reporter,
+        min_failing_severity,
+        None,
+        Some("load next.config.js"),
+    )
+    .await?;
+    let mut next_config = next_config.await?.clone_value();
+    if let Some(dist_dir) = &options.dist_dir {
+        next_config.dist_dir = Some(dist_dir.clone());
+    }
+    let dist_dir = project_path.join(next_config.dist_dir.as_deref().unwrap_or(".next"));
+
+    let (build_id, rewrites) = match &options.build_context {
+        Some(build_context) => (
+            build_context.build_id.clone(),
+            build_context.rewrites.clone(),
+        ),
+        None => {
+            let rewrites = load_rewrites(execution_context);
+            handle_issues(
+                rewrites,
+                issue_reporter,
+                min_failing_severity,
+                None,
+                Some("load rewrites"),
+            )
+            .await?;
+            (generate_build_id()?, rewrites.await?.clone_value())
+        }
+    };
+
+    let container = ProjectContainer::new(ProjectOptions {
+        root_path: root_path_str,
+        project_path: project_path_str,
+        next_config: serde_json::to_string(&next_config)?,
+        js_config: read_js_config(&project_path),
+        env: std::env::vars().collect(),
+        define_env: options.define_env.clone().into(),
+        watch: false,
+        dev: false,
+    });
+
+    let entrypoints = container.entrypoints();
+    handle_issues(
+        entrypoints,
+        issue_reporter,
+        min_failing_severity,
+        None,
+        Some("get entrypoints"),
+    )
+    .await?;
+    let entrypoints = entrypoints.strongly_consistent().await?;
+
+    let routes = entrypoints.routes.clone();
+
+    let mut endpoints: Vec<(String, Vc<Box<dyn Endpoint>>)> = vec![
+        (
+            "/_document".to_string(),
+            entrypoints.pages_document_endpoint,
+        ),
+        ("/_app".to_string(), entrypoints.pages_app_endpoint),
+        ("/_error".to_string(), entrypoints.pages_error_endpoint),
+    ];
+    for (pathname, route) in routes.iter() {
+        match *route {
+            // The data and RSC endpoints share their output with the HTML endpoint
+            // and are only needed to subscribe to changes in development.
+            Route::Page { html_endpoint, .. } | Route::AppPage { html_endpoint, .. } => {
+                endpoints.push((pathname.clone(), html_endpoint));
+            }
+            Route::PageApi { endpoint } | Route::AppRoute { endpoint } => {
+                endpoints.push((pathname.clone(), endpoint));
+            }
+            Route::Conflict => {
+                bail!("{pathname} is defined by both the pages and the app router");
+            }
+        }
+    }
+    if let Some(middleware) = &entrypoints.middleware {
+        endpoints.push(("middleware".to_string(), middleware.endpoint));
+    }
+    if let Some(instrumentation) = &entrypoints.instrumentation {
+        endpoints.push(("instrumentation".to_string(), instrumentation.node_js));
+        endpoints.push(("instrumentation (edge)".to_string(), instrumentation.edge));
+    }
+
+    // Only the export marker needs to know whether `next/image` is used.
+    let is_export = next_config.output == Some(OutputType::Export);
+    let written_endpoints = endpoints
+        .iter()
+        .map(|(name, endpoint)| async move {
+            let written_endpoint = endpoint.write_to_disk();
+            handle_issues(
+                written_endpoint,
+                issue_reporter,
+                min_failing_severity,
+                Some(name),
+                Some("write to disk"),
+            )
+            .await?;
+            let imports_next_image = is_export && imports_next_image(written_endpoint).await?;
+            Ok::<_, anyhow::Error>((
+                written_endpoint.strongly_consistent().await?,
+                imports_next_image,
+            ))
+        })
+        .try_join()
+        .await?;
+    let is_next_image_imported = written_endpoints
+        .iter()
+        .any(|(_, imports_next_image)| *imports_next_image);
+    let written_endpoints = written_endpoints
+        .into_iter()
+        .map(|(written_endpoint, _)| written_endpoint)
+        .collect::<Vec<_>>();
+
+    let mut partial_manifests = PartialManifests::default();
+    for written_endpoint in written_endpoints.iter() {
+        partial_manifests.collect(&dist_dir, written_endpoint)?;
+    }
+    let sorted_pages = routes
+        .iter()
+        .filter(|(_, route)| matches!(route, Route::Page { .. } | Route::PageApi { .. }))
+        .map(|(pathname, _)| pathname.clone())
+        .collect::<Vec<_>>();
+    partial_manifests.write(&dist_dir, &build_id, &rewrites, &sorted_pages)?;
+
+    let mut data_pages = vec![];
+    for (pathname, route) in routes.iter() {
+        if let Route::Page { exports, .. } = route {
+            let exports = exports.await?;
+            if exports.get_static_props || exports.get_server_side_props {
+                data_pages.push(pathname.clone());
+            }
+        }
+    }
+
+    let project = container.project();
+    let node_root = project.node_root();
+    let next_config = next_config.cell();
+    let routes_manifest = build_routes_manifest(
+        node_root,
+        next_config,
+        rewrites.clone().cell(),
+        load_redirects(execution_context),
+        load_headers(execution_context),
+        Vc::cell(routes.keys().cloned().collect()),
+        Vc::cell(data_pages),
+        build_id.clone(),
+    );
+    let images_manifest = build_images_manifest(node_root, next_config);
+    let prerender_manifest = build_prerender_manifest(
+        node_root,
+        next_config,
+        Vc::cell(routes),
+        build_id.clone(),
+        generate_preview_props(),
+    );
+    let mut output_assets = vec![routes_manifest, images_manifest];
+    output_assets.extend(prerender_manifest.await?.iter().copied());
+    let emitted = emit_assets(
+        Vc::cell(output_assets),
+        node_root,
+        project.client_relative_path(),
+        node_root,
+    );
+    handle_issues(
+        emitted,
+        issue_reporter,
+        min_failing_severity,
+        None,
+        Some("emit manifests"),
+    )
+    .await?;
+    emitted.strongly_consistent().await?;
+
+    std::fs::write(dist_dir.join("BUILD_ID"), &build_id).context("unable to write BUILD_ID")?;
+
+    let mut server_entries = vec![];
+    let mut edge_paths = vec![];
+    for ((name, _), written_endpoint) in endpoints.iter().zip(written_endpoints.iter()) {
+        match &**written_endpoint {
+            WrittenEndpoint::NodeJs {
+                server_entry_path,
+                server_paths,
+            } => server_entries.push(ServerEntry {
+                pathname: name,
+                entry_path: server_entry_path,
+                server_paths,
+            }),
+            WrittenEndpoint::Edge { server_paths } => edge_paths.extend(server_paths.clone()),
+        }
+    }
+    let next_config = next_config.await?;
+    FileTracing::new(
+        &next_config,
+        &root_path,
+        &project_path,
+        &dist_dir,
+        &turbo_tasks,
+    )?
+    .run(&server_entries, &edge_paths)
+    .await?;
+
+    if next_config.output == Some(OutputType::Export) {
+        write_export(
+            &next_config,
+            &project_path,
+            &dist_dir,
+            is_next_image_imported,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Whether a written endpoint imports `next/image`, as reported by the
+/// feature telemetry of the module resolution.
+async fn imports_next_image(written_endpoint: Vc<WrittenEndpoint>) -> Result<bool> {
+    let diagnostics = written_endpoint.peek_diagnostics().await?;
+    for diagnostic in diagnostics.diagnostics.iter() {
+        let payload = diagnostic.payload().await?;
+        if ["next/image", "next/future/image", "next/legacy/image"]
+            .iter()
+            .any(|feature| payload.contains_key(*feature))
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// The execution context used to evaluate next.config.js before the project
+/// itself can be created.
+#[turbo_tasks::function]
+fn project_execution_context(
+    root_path: String,
+    project_path: String,
+    dist_dir: String,
+) -> Vc<ExecutionContext> {
+    let project_relative = project_path.strip_prefix(&root_path).unwrap_or_default();
+    let project_relative = project_relative
+        .strip_prefix(MAIN_SEPARATOR)
+        .unwrap_or(project_relative)
+        .replace(MAIN_SEPARATOR, "/");
+    let project_fs = DiskFileSystem::new(PROJECT_FILESYSTEM_NAME.to_string(), root_path);
+    let project_root = project_fs.root().join(project_relative);
+
+    let node_fs = DiskFileSystem::new("node".to_string(), project_path);
+    let node_root = node_fs.root().join(dist_dir);
+
+    let chunking_context = DevChunkingContext::builder(
+        project_root,
+        node_root,
+        node_root,
+        node_root.join("chunks".to_string()),
+        node_root.join("assets".to_string()),
+        node_build_environment(),
+    )
+    .build();
+
+    ExecutionContext::new(
+        project_root,
+        Vc::upcast(chunking_context),
+        Vc::upcast(CommandLineProcessEnv::new()),
+    )
+}
+
+/// Reads the `compilerOptions` Next.js interops with from tsconfig.json or
+/// jsconfig.json. Configs which aren't plain JSON (comments, `extends`) are
+/// treated as empty, as they are only used for telemetry.
+fn read_js_config(project_path: &Path) -> String {
+    ["tsconfig.json", "jsconfig.json"]
+        .into_iter()
+        .find_map(|name| std::fs::read_to_string(project_path.join(name)).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .map(|config| serde_json::json!({ "compilerOptions": config["compilerOptions"] }))
+        .unwrap_or_else(|| serde_json::json!({ "compilerOptions": {} }))
+        .to_string()
+}
+
+/// Generates a build ID when none was provided by Next.js.
+fn generate_build_id() -> Result<String> {
+    Ok(format!(
+        "{:x}",
+        SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis()
+    ))
+}
+
+/// Generates new preview mode secrets for every build.
+fn generate_preview_props() -> PreviewProps {
+    fn random_hex<const N: usize>() -> String {
+        rand::random::<[u8; N]>()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    PreviewProps {
+        preview_mode_id: random_hex::<16>(),
+        preview_mode_signing_key: random_hex::<32>(),
+        preview_mode_encryption_key: random_hex::<32>(),
+    }
+}
+
+fn path_to_string(path: &Path) -> Result<String> {
+    Ok(path
+        .to_str()
+        .with_context(|| format!("{} contains invalid characters", path.display()))?
+        .to_string())
+}
+
+impl From<DefineEnv> for ProjectDefineEnv {
+    fn from(val: DefineEnv) -> Self {
+        ProjectDefineEnv {
+            client: val.client,
+            edge: val.edge,
+            nodejs: val.nodejs,
+        }
+    }
+}
@@ -353,6 +353,7 @@ async fn get_page_entry_for_file(
         todo_get_pages_structure(),
         NextRuntime::NodeJs,
         next_config,
+        NextMode::Build,
     );
 
     let client_module = create_page_loader_entry_module(client_module_context, source, pathname);
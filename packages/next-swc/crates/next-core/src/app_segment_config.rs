@@ -1,11 +1,14 @@
 use std::ops::Deref;
 
 use anyhow::{bail, Result};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use swc_core::{
     common::{source_map::Pos, Span, Spanned, GLOBALS},
-    ecma::ast::{Expr, Ident, Program},
+    ecma::ast::{
+        Decl, ExportSpecifier, Expr, Ident, Module, ModuleDecl, ModuleExportName, Program,
+    },
 };
 use turbo_tasks::{trace::TraceRawVcs, TryJoinIterExt, ValueDefault, Vc};
 use turbo_tasks_fs::FileSystemPath;
@@ -70,6 +73,9 @@ pub struct NextSegmentConfig {
     pub fetch_cache: Option<NextSegmentFetchCache>,
     pub runtime: Option<NextRuntime>,
     pub preferred_region: Option<Vec<String>>,
+    /// The maximum duration of the function, in seconds.
+    pub max_duration: Option<u32>,
+    pub experimental_ppr: Option<bool>,
 }
 
 #[turbo_tasks::value_impl]
@@ -91,6 +97,8 @@ impl NextSegmentConfig {
             fetch_cache,
             runtime,
             preferred_region,
+            max_duration,
+            experimental_ppr,
         } = self;
         *dynamic = dynamic.or(parent.dynamic);
         *dynamic_params = dynamic_params.or(parent.dynamic_params);
@@ -98,6 +106,8 @@ impl NextSegmentConfig {
         *fetch_cache = fetch_cache.or(parent.fetch_cache);
         *runtime = runtime.or(parent.runtime);
         *preferred_region = preferred_region.take().or(parent.preferred_region.clone());
+        *max_duration = max_duration.or(parent.max_duration);
+        *experimental_ppr = experimental_ppr.or(parent.experimental_ppr);
     }
 
     /// Applies a config from a paralllel route to this config, returning an
@@ -131,6 +141,8 @@ impl NextSegmentConfig {
             fetch_cache,
             runtime,
             preferred_region,
+            max_duration,
+            experimental_ppr,
         } = self;
         merge_parallel(dynamic, &parallel_config.dynamic, "dynamic")?;
         merge_parallel(
@@ -146,6 +158,12 @@ impl NextSegmentConfig {
             &parallel_config.preferred_region,
             "referredRegion",
         )?;
+        merge_parallel(max_duration, &parallel_config.max_duration, "maxDuration")?;
+        merge_parallel(
+            experimental_ppr,
+            &parallel_config.experimental_ppr,
+            "experimental_ppr",
+        )?;
         Ok(())
     }
 }
@@ -215,36 +233,10 @@ impl Issue for NextSegmentConfigParsingIssue {
 pub async fn parse_segment_config_from_source(
     source: Vc<Box<dyn Source>>,
 ) -> Result<Vc<NextSegmentConfig>> {
-    let path = source.ident().path().await?;
-
-    // Don't try parsing if it's not a javascript file, otherwise it will emit an
-    // issue causing the build to "fail".
-    if !(path.path.ends_with(".js")
-        || path.path.ends_with(".jsx")
-        || path.path.ends_with(".ts")
-        || path.path.ends_with(".tsx"))
-    {
+    let Some(result) = parse_js_source(source).await? else {
         return Ok(Default::default());
-    }
-
-    let result = &*parse(
-        source,
-        turbo_tasks::Value::new(if path.path.ends_with(".ts") {
-            EcmascriptModuleAssetType::Typescript {
-                tsx: false,
-                analyze_types: false,
-            }
-        } else if path.path.ends_with(".tsx") {
-            EcmascriptModuleAssetType::Typescript {
-                tsx: true,
-                analyze_types: false,
-            }
-        } else {
-            EcmascriptModuleAssetType::Ecmascript
-        }),
-        EcmascriptInputTransforms::empty(),
-    )
-    .await?;
+    };
+    let result = &*result.await?;
 
     let ParseResult::Ok {
         program: Program::Module(module_ast),
@@ -284,6 +276,101 @@ pub async fn parse_segment_config_from_source(
     Ok(config.cell())
 }
 
+/// Points issues about the export `name` of `source` at its declaration, or
+/// at the start of the file if it can't be found. `"default"` stands for the
+/// default export.
+#[turbo_tasks::function]
+pub async fn export_issue_source(
+    source: Vc<Box<dyn Source>>,
+    name: String,
+) -> Result<Vc<IssueSource>> {
+    let span = match parse_js_source(source).await? {
+        Some(result) => match &*result.await? {
+            ParseResult::Ok {
+                program: Program::Module(module_ast),
+                ..
+            } => export_span(module_ast, &name),
+            _ => None,
+        },
+        None => None,
+    };
+
+    Ok(match span {
+        Some(span) => issue_source(source, span),
+        None => IssueSource::from_byte_offset(source, 0, 0),
+    })
+}
+
+fn export_span(module_ast: &Module, name: &str) -> Option<Span> {
+    module_ast
+        .body
+        .iter()
+        .find_map(|item| match item.as_module_decl()? {
+            ModuleDecl::ExportDefaultDecl(decl) if name == "default" => Some(decl.span),
+            ModuleDecl::ExportDefaultExpr(expr) if name == "default" => Some(expr.span),
+            ModuleDecl::ExportDecl(export_decl) => match &export_decl.decl {
+                Decl::Fn(decl) => (&*decl.ident.sym == name).then_some(export_decl.span),
+                Decl::Var(decl) => decl
+                    .decls
+                    .iter()
+                    .find(|decl| {
+                        decl.name
+                            .as_ident()
+                            .map_or(false, |ident| &*ident.sym == name)
+                    })
+                    .map(|decl| decl.span()),
+                _ => None,
+            },
+            ModuleDecl::ExportNamed(named_export) => {
+                named_export.specifiers.iter().find_map(|specifier| {
+                    let ExportSpecifier::Named(specifier) = specifier else {
+                        return None;
+                    };
+                    let exported = match specifier.exported.as_ref().unwrap_or(&specifier.orig) {
+                        ModuleExportName::Ident(ident) => &ident.sym,
+                        ModuleExportName::Str(str) => &str.value,
+                    };
+                    (&**exported == name).then_some(specifier.span)
+                })
+            }
+            _ => None,
+        })
+}
+
+/// Parses a JavaScript or TypeScript source. Returns `None` for any other
+/// file type.
+async fn parse_js_source(source: Vc<Box<dyn Source>>) -> Result<Option<Vc<ParseResult>>> {
+    let path = source.ident().path().await?;
+
+    // Don't try parsing if it's not a javascript file, otherwise it will emit an
+    // issue causing the build to "fail".
+    if !(path.path.ends_with(".js")
+        || path.path.ends_with(".jsx")
+        || path.path.ends_with(".ts")
+        || path.path.ends_with(".tsx"))
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(parse(
+        source,
+        turbo_tasks::Value::new(if path.path.ends_with(".ts") {
+            EcmascriptModuleAssetType::Typescript {
+                tsx: false,
+                analyze_types: false,
+            }
+        } else if path.path.ends_with(".tsx") {
+            EcmascriptModuleAssetType::Typescript {
+                tsx: true,
+                analyze_types: false,
+            }
+        } else {
+            EcmascriptModuleAssetType::Ecmascript
+        }),
+        EcmascriptInputTransforms::empty(),
+    )))
+}
+
 fn issue_source(source: Vc<Box<dyn Source>>, span: Span) -> Vc<IssueSource> {
     IssueSource::from_byte_offset(source, span.lo.to_usize(), span.hi.to_usize())
 }
@@ -420,6 +507,29 @@ fn parse_config_value(
 
             config.preferred_region = Some(preferred_region);
         }
+        "maxDuration" => {
+            let value = eval_context.eval(init);
+            match value {
+                JsValue::Constant(ConstantValue::Num(ConstantNumber(val)))
+                    if val >= 0.0 && val.fract() == 0.0 && val <= u32::MAX as f64 =>
+                {
+                    config.max_duration = Some(val as u32);
+                }
+                _ => invalid_config(
+                    "`maxDuration` needs to be a static positive integer",
+                    &value,
+                ),
+            }
+        }
+        "experimental_ppr" => {
+            let value = eval_context.eval(init);
+            let Some(val) = value.as_bool() else {
+                invalid_config("`experimental_ppr` needs to be a static boolean", &value);
+                return;
+            };
+
+            config.experimental_ppr = Some(val);
+        }
         _ => {}
     }
 }
@@ -451,3 +561,132 @@ pub async fn parse_segment_config_from_loader_tree(
 
     Ok(config.cell())
 }
+
+/// The exports of a segment's page and layout, or of a pages router page,
+/// which are relevant for static generation.
+#[turbo_tasks::value]
+#[derive(Debug, Default)]
+pub struct NextSegmentExports {
+    pub generate_static_params: bool,
+    pub generate_metadata: bool,
+    /// Only used by the pages router.
+    pub get_static_props: bool,
+    /// Only used by the pages router.
+    pub get_server_side_props: bool,
+}
+
+#[turbo_tasks::function]
+pub async fn parse_segment_exports_from_source(
+    source: Vc<Box<dyn Source>>,
+) -> Result<Vc<NextSegmentExports>> {
+    let mut exports = NextSegmentExports::default();
+
+    let Some(result) = parse_js_source(source).await? else {
+        return Ok(exports.cell());
+    };
+    let result = result.await?;
+    let ParseResult::Ok {
+        program: Program::Module(module_ast),
+        ..
+    } = &*result
+    else {
+        return Ok(exports.cell());
+    };
+
+    let mut record_export = |name: &str| match name {
+        "generateStaticParams" => exports.generate_static_params = true,
+        "generateMetadata" => exports.generate_metadata = true,
+        "getStaticProps" => exports.get_static_props = true,
+        "getServerSideProps" => exports.get_server_side_props = true,
+        _ => {}
+    };
+    for item in &module_ast.body {
+        match item.as_module_decl() {
+            Some(ModuleDecl::ExportDecl(export_decl)) => match &export_decl.decl {
+                Decl::Fn(decl) => record_export(&decl.ident.sym),
+                Decl::Var(decl) => {
+                    for decl in &decl.decls {
+                        if let Some(ident) = decl.name.as_ident() {
+                            record_export(&ident.sym);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Some(ModuleDecl::ExportNamed(named_export)) => {
+                for specifier in &named_export.specifiers {
+                    if let ExportSpecifier::Named(specifier) = specifier {
+                        match specifier.exported.as_ref().unwrap_or(&specifier.orig) {
+                            ModuleExportName::Ident(ident) => record_export(&ident.sym),
+                            ModuleExportName::Str(str) => record_export(&str.value),
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(exports.cell())
+}
+
+/// The static generation related exports of each node in a [LoaderTree].
+#[turbo_tasks::value]
+#[derive(Debug)]
+pub struct LoaderTreeSegmentExports {
+    pub segment: String,
+    /// Whether the node's page or layout exports `generateStaticParams`.
+    pub generate_static_params: bool,
+    /// Whether the node's page or layout exports `generateMetadata`.
+    pub generate_metadata: bool,
+    pub parallel_routes: IndexMap<String, Vc<LoaderTreeSegmentExports>>,
+}
+
+#[turbo_tasks::value_impl]
+impl LoaderTreeSegmentExports {
+    /// Returns true if any node in the tree exports `generateStaticParams`.
+    #[turbo_tasks::function]
+    pub async fn has_generate_static_params(&self) -> Result<Vc<bool>> {
+        if self.generate_static_params {
+            return Ok(Vc::cell(true));
+        }
+
+        for tree in self.parallel_routes.values() {
+            if *tree.has_generate_static_params().await? {
+                return Ok(Vc::cell(true));
+            }
+        }
+
+        Ok(Vc::cell(false))
+    }
+}
+
+#[turbo_tasks::function]
+pub async fn parse_segment_exports_from_loader_tree(
+    loader_tree: Vc<LoaderTree>,
+) -> Result<Vc<LoaderTreeSegmentExports>> {
+    let loader_tree = loader_tree.await?;
+    let components = loader_tree.components.await?;
+    let parallel_routes = loader_tree
+        .parallel_routes
+        .iter()
+        .map(|(key, &tree)| (key.clone(), parse_segment_exports_from_loader_tree(tree)))
+        .collect();
+
+    let mut generate_static_params = false;
+    let mut generate_metadata = false;
+    for component in [components.page, components.layout].into_iter().flatten() {
+        let source = Vc::upcast(FileSource::new(component));
+        let exports = parse_segment_exports_from_source(source).await?;
+        generate_static_params |= exports.generate_static_params;
+        generate_metadata |= exports.generate_metadata;
+    }
+
+    Ok(LoaderTreeSegmentExports {
+        segment: loader_tree.segment.clone(),
+        generate_static_params,
+        generate_metadata,
+        parallel_routes,
+    }
+    .cell())
+}
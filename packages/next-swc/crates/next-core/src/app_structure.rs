@@ -13,7 +13,7 @@ use turbo_tasks::{
     Vc,
 };
 use turbopack_binding::{
-    turbo::tasks_fs::{DirectoryContent, DirectoryEntry, FileSystemEntryType, FileSystemPath},
+    turbo::tasks_fs::{DirectoryContent, FileSystemEntryType, FileSystemPath},
     turbopack::core::issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
 };
 
@@ -23,10 +23,11 @@ use crate::{
             match_global_metadata_file, match_local_metadata_file, normalize_metadata_route,
             GlobalMetadataFileMatch, MetadataFileMatch,
         },
-        AppPage, AppPath, PageType,
+        AppPage, AppPath, PageSegment, PageType,
     },
     next_config::NextConfig,
     next_import_map::get_next_package,
+    util::{follow_symlink, FollowedEntry},
 };
 
 /// A final route in the app directory.
@@ -49,6 +50,9 @@ pub struct Components {
     pub default: Option<Vc<FileSystemPath>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub route: Option<Vc<FileSystemPath>>,
+    /// Only honored at the root of the app directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global_error: Option<Vc<FileSystemPath>>,
     #[serde(skip_serializing_if = "Metadata::is_empty")]
     pub metadata: Metadata,
 }
@@ -64,6 +68,7 @@ impl Components {
             not_found: self.not_found,
             default: None,
             route: None,
+            global_error: self.global_error,
             metadata: self.metadata.clone(),
         }
     }
@@ -278,15 +283,26 @@ pub async fn find_app_dir_if_enabled(project_path: Vc<FileSystemPath>) -> Result
 }
 
 #[turbo_tasks::function]
-async fn get_directory_tree(
+fn get_directory_tree(
+    dir: Vc<FileSystemPath>,
+    page_extensions: Vc<Vec<String>>,
+) -> Vc<DirectoryTree> {
+    get_subdirectory_tree(dir, page_extensions, Vc::cell(vec![]))
+}
+
+/// `symlinked_from` are the directories whose symlinks were followed to reach
+/// `dir`, see [follow_symlink].
+#[turbo_tasks::function]
+async fn get_subdirectory_tree(
     dir: Vc<FileSystemPath>,
     page_extensions: Vc<Vec<String>>,
+    symlinked_from: Vc<Vec<String>>,
 ) -> Result<Vc<DirectoryTree>> {
     let span = {
         let dir = dir.to_string().await?;
         tracing::info_span!("read app directory tree", name = *dir)
     };
-    get_directory_tree_internal(dir, page_extensions)
+    get_directory_tree_internal(dir, page_extensions, symlinked_from)
         .instrument(span)
         .await
 }
@@ -294,6 +310,7 @@ async fn get_directory_tree(
 async fn get_directory_tree_internal(
     dir: Vc<FileSystemPath>,
     page_extensions: Vc<Vec<String>>,
+    symlinked_from: Vc<Vec<String>>,
 ) -> Result<Vc<DirectoryTree>> {
     let DirectoryContent::Entries(entries) = &*dir.read_dir().await? else {
         bail!("{} must be a directory", dir.to_string().await?);
@@ -309,8 +326,11 @@ async fn get_directory_tree_internal(
     let mut metadata_twitter = Vec::new();
 
     for (basename, entry) in entries {
-        match *entry {
-            DirectoryEntry::File(file) => {
+        let Some(entry) = follow_symlink(dir, entry, symlinked_from).await? else {
+            continue;
+        };
+        match entry {
+            FollowedEntry::File(file) => {
                 let file = file.resolve().await?;
                 if let Some((stem, ext)) = basename.split_once('.') {
                     if page_extensions_value.iter().any(|e| e == ext) {
@@ -323,6 +343,7 @@ async fn get_directory_tree_internal(
                             "not-found" => components.not_found = Some(file),
                             "default" => components.default = Some(file),
                             "route" => components.route = Some(file),
+                            "global-error" => components.global_error = Some(file),
                             _ => {}
                         }
                     }
@@ -376,16 +397,14 @@ async fn get_directory_tree_internal(
                     },
                 ));
             }
-            DirectoryEntry::Directory(dir) => {
+            FollowedEntry::Directory(dir, symlinked_from) => {
                 let dir = dir.resolve().await?;
                 // appDir ignores paths starting with an underscore
                 if !basename.starts_with('_') {
-                    let result = get_directory_tree(dir, page_extensions);
+                    let result = get_subdirectory_tree(dir, page_extensions, symlinked_from);
                     subdirectories.insert(get_underscore_normalized_path(basename), result);
                 }
             }
-            // TODO(WEB-952) handle symlinks in app dir
-            _ => {}
         }
     }
 
@@ -468,37 +487,23 @@ fn match_parallel_route(name: &str) -> Option<&str> {
     name.strip_prefix('@')
 }
 
-fn conflict_issue(
-    app_dir: Vc<FileSystemPath>,
+/// The entrypoints which conflict with the one in the result, by path.
+type Conflicts = IndexMap<AppPath, Vec<Entrypoint>>;
+
+fn add_conflict(
+    conflicts: &mut Conflicts,
     e: &OccupiedEntry<AppPath, Entrypoint>,
-    a: &str,
-    b: &str,
-    value_a: &AppPage,
-    value_b: &AppPage,
+    entrypoint: Entrypoint,
 ) {
-    let item_names = if a == b {
-        format!("{}s", a)
-    } else {
-        format!("{} and {}", a, b)
-    };
-
-    DirectoryTreeIssue {
-        app_dir,
-        message: StyledString::Text(format!(
-            "Conflicting {} at {}: {a} at {value_a} and {b} at {value_b}",
-            item_names,
-            e.key(),
-        ))
-        .cell(),
-        severity: IssueSeverity::Error.cell(),
-    }
-    .cell()
-    .emit();
+    conflicts
+        .entry(e.key().clone())
+        .or_default()
+        .push(entrypoint);
 }
 
 async fn add_app_page(
-    app_dir: Vc<FileSystemPath>,
     result: &mut IndexMap<AppPath, Entrypoint>,
+    conflicts: &mut Conflicts,
     page: AppPage,
     loader_tree: Vc<LoaderTree>,
 ) -> Result<()> {
@@ -510,132 +515,72 @@ async fn add_app_page(
         }
     };
 
-    let conflict = |existing_name: &str, existing_page: &AppPage| {
-        conflict_issue(app_dir, &e, "page", existing_name, &page, existing_page);
+    let Entrypoint::AppPage {
+        loader_tree: existing_loader_tree,
+        ..
+    } = e.get()
+    else {
+        add_conflict(conflicts, &e, Entrypoint::AppPage { page, loader_tree });
+        return Ok(());
     };
 
-    let value = e.get();
-    match value {
-        Entrypoint::AppPage {
-            page: existing_page,
-            loader_tree: existing_loader_tree,
-        } => {
-            // loader trees should always match for the same path as they are generated by a
-            // turbo tasks function
-            if *existing_loader_tree != loader_tree {
-                conflict("page", existing_page);
-            }
+    // loader trees should always match for the same path as they are generated by a
+    // turbo tasks function
+    if *existing_loader_tree != loader_tree {
+        add_conflict(
+            conflicts,
+            &e,
+            Entrypoint::AppPage {
+                page: page.clone(),
+                loader_tree,
+            },
+        );
+    }
 
-            let Entrypoint::AppPage {
-                page: stored_page, ..
-            } = e.get_mut()
-            else {
-                unreachable!("Entrypoint::AppPage was already matched");
-            };
+    let Entrypoint::AppPage {
+        page: stored_page, ..
+    } = e.get_mut()
+    else {
+        unreachable!("Entrypoint::AppPage was already matched");
+    };
 
-            // next.js does some weird stuff when looking up routes so we have to emit the
-            // correct path (shortest segments, but alphabetically the last).
-            if page.len() < stored_page.len()
-                || (page.len() == stored_page.len() && page.to_string() > stored_page.to_string())
-            {
-                *stored_page = page;
-            }
-        }
-        Entrypoint::AppRoute {
-            page: existing_page,
-            ..
-        } => {
-            conflict("route", existing_page);
-        }
-        Entrypoint::AppMetadata {
-            page: existing_page,
-            ..
-        } => {
-            conflict("metadata", existing_page);
-        }
+    // next.js does some weird stuff when looking up routes so we have to emit the
+    // correct path (shortest segments, but alphabetically the last).
+    if page.len() < stored_page.len()
+        || (page.len() == stored_page.len() && page.to_string() > stored_page.to_string())
+    {
+        *stored_page = page;
     }
 
     Ok(())
 }
 
 fn add_app_route(
-    app_dir: Vc<FileSystemPath>,
     result: &mut IndexMap<AppPath, Entrypoint>,
+    conflicts: &mut Conflicts,
     page: AppPage,
     path: Vc<FileSystemPath>,
 ) {
-    let e = match result.entry(page.clone().into()) {
-        Entry::Occupied(e) => e,
+    match result.entry(page.clone().into()) {
+        Entry::Occupied(e) => add_conflict(conflicts, &e, Entrypoint::AppRoute { page, path }),
         Entry::Vacant(e) => {
             e.insert(Entrypoint::AppRoute { page, path });
-            return;
-        }
-    };
-
-    let conflict = |existing_name: &str, existing_page: &AppPage| {
-        conflict_issue(app_dir, &e, "route", existing_name, &page, existing_page);
-    };
-
-    let value = e.get();
-    match value {
-        Entrypoint::AppPage {
-            page: existing_page,
-            ..
-        } => {
-            conflict("page", existing_page);
-        }
-        Entrypoint::AppRoute {
-            page: existing_page,
-            ..
-        } => {
-            conflict("route", existing_page);
-        }
-        Entrypoint::AppMetadata {
-            page: existing_page,
-            ..
-        } => {
-            conflict("metadata", existing_page);
         }
     }
 }
 
 fn add_app_metadata_route(
-    app_dir: Vc<FileSystemPath>,
     result: &mut IndexMap<AppPath, Entrypoint>,
+    conflicts: &mut Conflicts,
     page: AppPage,
     metadata: MetadataItem,
 ) {
-    let e = match result.entry(page.clone().into()) {
-        Entry::Occupied(e) => e,
+    match result.entry(page.clone().into()) {
+        Entry::Occupied(e) => {
+            add_conflict(conflicts, &e, Entrypoint::AppMetadata { page, metadata })
+        }
         Entry::Vacant(e) => {
             e.insert(Entrypoint::AppMetadata { page, metadata });
-            return;
-        }
-    };
-
-    let conflict = |existing_name: &str, existing_page: &AppPage| {
-        conflict_issue(app_dir, &e, "metadata", existing_name, &page, existing_page);
-    };
-
-    let value = e.get();
-    match value {
-        Entrypoint::AppPage {
-            page: existing_page,
-            ..
-        } => {
-            conflict("page", existing_page);
-        }
-        Entrypoint::AppRoute {
-            page: existing_page,
-            ..
-        } => {
-            conflict("route", existing_page);
-        }
-        Entrypoint::AppMetadata {
-            page: existing_page,
-            ..
-        } => {
-            conflict("metadata", existing_page);
         }
     }
 }
@@ -703,6 +648,10 @@ async fn directory_tree_to_loader_tree(
     // the path).
     let is_root_layout = app_path.is_root() && components.layout.is_some();
 
+    if !is_root_directory {
+        components.global_error = None;
+    }
+
     if (is_root_directory || is_root_layout) && components.not_found.is_none() {
         components.not_found = Some(
             get_next_package(app_dir).join("dist/client/components/not-found-error.js".to_string()),
@@ -760,6 +709,7 @@ async fn directory_tree_to_loader_tree(
         }
     }
 
+    let mut conflicting_children = vec![];
     for (subdir_name, subdirectory) in &directory_tree.subdirectories {
         let parallel_route_key = match_parallel_route(subdir_name);
 
@@ -798,22 +748,13 @@ async fn directory_tree_to_loader_tree(
                 continue;
             }
 
-            if !tree.parallel_routes.contains_key("children") {
-                tree.parallel_routes.insert("children".to_string(), subtree);
+            if tree.parallel_routes.contains_key("children") {
+                conflicting_children.push(Entrypoint::AppPage {
+                    page: child_app_page,
+                    loader_tree: subtree,
+                });
             } else {
-                // TODO: improve error message to have the full paths
-                DirectoryTreeIssue {
-                    app_dir,
-                    message: StyledString::Text(format!(
-                        "You cannot have two parallel pages that resolve to the same path. Route \
-                         {} has multiple matches in {}",
-                        for_app_path, app_page
-                    ))
-                    .cell(),
-                    severity: IssueSeverity::Error.cell(),
-                }
-                .cell()
-                .emit();
+                tree.parallel_routes.insert("children".to_string(), subtree);
             }
         } else if let Some(key) = parallel_route_key {
             bail!(
@@ -824,6 +765,22 @@ async fn directory_tree_to_loader_tree(
         }
     }
 
+    if !conflicting_children.is_empty() {
+        let children = tree.parallel_routes["children"];
+        let mut entrypoints = vec![Entrypoint::AppPage {
+            page: children.await?.page.clone(),
+            loader_tree: children,
+        }];
+        entrypoints.extend(conflicting_children);
+        RouteConflictIssue {
+            app_dir,
+            app_path: for_app_path.clone(),
+            entrypoints,
+        }
+        .cell()
+        .emit();
+    }
+
     if tree.parallel_routes.is_empty() {
         tree.segment = "__DEFAULT__".to_string();
         if let Some(default) = components.default {
@@ -907,6 +864,7 @@ async fn directory_tree_to_entrypoints_internal_untraced(
     app_page: AppPage,
 ) -> Result<Vc<Entrypoints>> {
     let mut result = IndexMap::new();
+    let mut conflicts = Conflicts::new();
 
     let directory_tree_vc = directory_tree;
     let directory_tree = &*directory_tree.await?;
@@ -929,8 +887,8 @@ async fn directory_tree_to_entrypoints_internal_untraced(
         .await?;
 
         add_app_page(
-            app_dir,
             &mut result,
+            &mut conflicts,
             app_page.complete(PageType::Page)?,
             loader_tree.context("loader tree should be created for a page/default")?,
         )
@@ -939,8 +897,8 @@ async fn directory_tree_to_entrypoints_internal_untraced(
 
     if let Some(route) = components.route {
         add_app_route(
-            app_dir,
             &mut result,
+            &mut conflicts,
             app_page.complete(PageType::Route)?,
             route,
         );
@@ -966,8 +924,8 @@ async fn directory_tree_to_entrypoints_internal_untraced(
         let app_page = app_page.clone_push_str(&get_metadata_route_name(meta).await?)?;
 
         add_app_metadata_route(
-            app_dir,
             &mut result,
+            &mut conflicts,
             normalize_metadata_route(app_page)?,
             meta,
         );
@@ -985,8 +943,8 @@ async fn directory_tree_to_entrypoints_internal_untraced(
             let app_page = app_page.clone_push_str(&get_metadata_route_name(*meta).await?)?;
 
             add_app_metadata_route(
-                app_dir,
                 &mut result,
+                &mut conflicts,
                 normalize_metadata_route(app_page)?,
                 *meta,
             );
@@ -1038,11 +996,11 @@ async fn directory_tree_to_entrypoints_internal_untraced(
 
         {
             let app_page = app_page.clone_push_str("not-found")?;
-            add_app_page(app_dir, &mut result, app_page, not_found_tree).await?;
+            add_app_page(&mut result, &mut conflicts, app_page, not_found_tree).await?;
         }
         {
             let app_page = app_page.clone_push_str("_not-found")?;
-            add_app_page(app_dir, &mut result, app_page, not_found_tree).await?;
+            add_app_page(&mut result, &mut conflicts, app_page, not_found_tree).await?;
         }
     }
 
@@ -1091,22 +1049,35 @@ async fn directory_tree_to_entrypoints_internal_untraced(
                     .await?;
 
                     add_app_page(
-                        app_dir,
                         &mut result,
+                        &mut conflicts,
                         page.clone(),
                         loader_tree.context("loader tree should be created for a page/default")?,
                     )
                     .await?;
                 }
                 Entrypoint::AppRoute { ref page, path } => {
-                    add_app_route(app_dir, &mut result, page.clone(), path);
+                    add_app_route(&mut result, &mut conflicts, page.clone(), path);
                 }
                 Entrypoint::AppMetadata { ref page, metadata } => {
-                    add_app_metadata_route(app_dir, &mut result, page.clone(), metadata);
+                    add_app_metadata_route(&mut result, &mut conflicts, page.clone(), metadata);
                 }
             }
         }
     }
+
+    for (app_path, conflicting) in conflicts {
+        let mut entrypoints = vec![result[&app_path].clone()];
+        entrypoints.extend(conflicting);
+        RouteConflictIssue {
+            app_dir,
+            app_path,
+            entrypoints,
+        }
+        .cell()
+        .emit();
+    }
+
     Ok(Vc::cell(result))
 }
 
@@ -1128,10 +1099,6 @@ pub async fn get_global_metadata(
     let mut metadata = GlobalMetadata::default();
 
     for (basename, entry) in entries {
-        let DirectoryEntry::File(file) = *entry else {
-            continue;
-        };
-
         let Some(GlobalMetadataFileMatch {
             metadata_type,
             dynamic,
@@ -1140,6 +1107,12 @@ pub async fn get_global_metadata(
             continue;
         };
 
+        let Some(FollowedEntry::File(file)) =
+            follow_symlink(app_dir, entry, Vc::cell(vec![])).await?
+        else {
+            continue;
+        };
+
         let entry = match metadata_type {
             "favicon" => &mut metadata.favicon,
             "manifest" => &mut metadata.manifest,
@@ -1152,32 +1125,29 @@ pub async fn get_global_metadata(
         } else {
             *entry = Some(MetadataItem::Static { path: file });
         }
-        // TODO(WEB-952) handle symlinks in app dir
     }
 
     Ok(metadata.cell())
 }
 
+/// Pages, routes or metadata files which resolve to the same path.
 #[turbo_tasks::value(shared)]
-struct DirectoryTreeIssue {
-    pub severity: Vc<IssueSeverity>,
-    pub app_dir: Vc<FileSystemPath>,
-    pub message: Vc<StyledString>,
+struct RouteConflictIssue {
+    app_dir: Vc<FileSystemPath>,
+    app_path: AppPath,
+    entrypoints: Vec<Entrypoint>,
 }
 
 #[turbo_tasks::value_impl]
-impl Issue for DirectoryTreeIssue {
+impl Issue for RouteConflictIssue {
     #[turbo_tasks::function]
     fn severity(&self) -> Vc<IssueSeverity> {
-        self.severity
+        IssueSeverity::Error.into()
     }
 
     #[turbo_tasks::function]
-    async fn title(&self) -> Result<Vc<StyledString>> {
-        Ok(
-            StyledString::Text("An issue occurred while preparing your Next.js app".to_string())
-                .cell(),
-        )
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(format!("Conflicting routes at {}", self.app_path)).cell()
     }
 
     #[turbo_tasks::function]
@@ -1191,7 +1161,82 @@ impl Issue for DirectoryTreeIssue {
     }
 
     #[turbo_tasks::function]
-    fn description(&self) -> Vc<OptionStyledString> {
-        Vc::cell(Some(self.message))
+    async fn description(&self) -> Result<Vc<OptionStyledString>> {
+        let mut lines = vec![StyledString::Text(format!(
+            "These files all resolve to {}:",
+            self.app_path
+        ))];
+
+        for entrypoint in &self.entrypoints {
+            let (kind, file, page) = match entrypoint {
+                Entrypoint::AppPage { page, loader_tree } => match page_file(*loader_tree).await? {
+                    Some((kind, file, page)) => (kind, Some(file), page),
+                    None => ("page", None, page.clone()),
+                },
+                Entrypoint::AppRoute { page, path } => ("route", Some(*path), page.clone()),
+                Entrypoint::AppMetadata { page, metadata } => {
+                    let (MetadataItem::Static { path } | MetadataItem::Dynamic { path }) = metadata;
+                    ("metadata", Some(*path), page.clone())
+                }
+            };
+
+            let location = match file {
+                Some(file) => file.to_string().await?.clone_value(),
+                None => page.to_string(),
+            };
+            let route_groups = page
+                .iter()
+                .filter(|segment| matches!(segment, PageSegment::Group(_)))
+                .map(|segment| segment.to_string())
+                .collect::<Vec<_>>();
+            let route_group = if route_groups.is_empty() {
+                "not in a route group".to_string()
+            } else {
+                format!("in route group {}", route_groups.join("/"))
+            };
+
+            lines.push(StyledString::Line(vec![
+                StyledString::Text(format!("- {kind} ")),
+                StyledString::Code(location),
+                StyledString::Text(format!(", {route_group}")),
+            ]));
+        }
+
+        lines.push(StyledString::Text(
+            "Route groups are omitted from the path, so a path can only be handled by one page or \
+             route across all of them."
+                .to_string(),
+        ));
+
+        Ok(Vc::cell(Some(StyledString::Stack(lines).cell())))
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell(
+            "https://nextjs.org/docs/app/building-your-application/routing/route-groups#good-to-know"
+                .to_string(),
+        )
+    }
+}
+
+/// Finds the `page` or `default` file which handles `loader_tree`, and the page
+/// it belongs to.
+async fn page_file(
+    mut loader_tree: Vc<LoaderTree>,
+) -> Result<Option<(&'static str, Vc<FileSystemPath>, AppPage)>> {
+    loop {
+        let tree = loader_tree.await?;
+        let components = tree.components.await?;
+        if let Some(page) = components.page {
+            return Ok(Some(("page", page, tree.page.clone())));
+        }
+        if let Some(default) = components.default {
+            return Ok(Some(("default", default, tree.page.clone())));
+        }
+        let Some(&children) = tree.parallel_routes.get("children") else {
+            return Ok(None);
+        };
+        loader_tree = children;
     }
 }
@@ -16,6 +16,7 @@ mod loader_tree;
 pub mod middleware;
 pub mod mode;
 pub mod next_app;
+mod next_barrel;
 mod next_build;
 pub mod next_client;
 pub mod next_client_reference;
@@ -34,14 +35,19 @@ mod next_shared;
 pub mod next_telemetry;
 mod page_loader;
 pub mod pages_structure;
+mod path_to_regexp;
 mod sass;
 pub mod tracing_presets;
 mod transform_options;
+pub mod typed_routes;
 pub mod url_node;
 pub mod util;
 
 pub use app_segment_config::{
-    parse_segment_config_from_loader_tree, parse_segment_config_from_source,
+    export_issue_source, parse_segment_config_from_loader_tree, parse_segment_config_from_source,
+    parse_segment_exports_from_loader_tree, parse_segment_exports_from_source,
+    LoaderTreeSegmentExports, NextRevalidate, NextSegmentConfig, NextSegmentDynamic,
+    NextSegmentExports,
 };
 pub use emit::{all_assets_from_entries, emit_all_assets, emit_assets, emit_client_assets};
 pub use next_edge::context::{
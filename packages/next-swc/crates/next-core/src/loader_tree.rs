@@ -33,6 +33,9 @@ use crate::{
     next_image::module::{BlurPlaceholderMode, StructuredImageModuleType},
 };
 
+/// The inner asset of the app's `global-error` component, if it has one.
+pub const GLOBAL_ERROR: &str = "GLOBAL_ERROR_MODULE";
+
 pub struct LoaderTreeBuilder {
     inner_assets: IndexMap<String, Vc<Box<dyn Module>>>,
     counter: usize,
@@ -400,6 +403,7 @@ impl LoaderTreeBuilder {
             not_found,
             metadata,
             route: _,
+            global_error: _,
         } = &*components.await?;
         self.write_component(ComponentType::Layout, *layout).await?;
         self.write_component(ComponentType::Page, *page).await?;
@@ -439,6 +443,19 @@ impl LoaderTreeBuilder {
     }
 
     async fn build(mut self, loader_tree: Vc<LoaderTree>) -> Result<LoaderTreeModule> {
+        let components = loader_tree.await?.components.await?;
+        if let Some(global_error) = components.global_error {
+            let source = Vc::upcast(FileSource::new(global_error));
+            let reference_ty = Value::new(ReferenceType::EcmaScriptModules(
+                EcmaScriptModulesReferenceSubType::Undefined,
+            ));
+            let module = self
+                .server_component_transition
+                .process(source, self.context, reference_ty)
+                .module();
+            self.inner_assets.insert(GLOBAL_ERROR.to_string(), module);
+        }
+
         self.walk_tree(loader_tree, true).await?;
         Ok(LoaderTreeModule {
             imports: self.imports,
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use indexmap::indexmap;
 use turbo_tasks::{Value, Vc};
 use turbo_tasks_fs::FileSystemPath;
@@ -6,7 +6,12 @@ use turbopack_binding::turbopack::core::{
     context::AssetContext, module::Module, reference_type::ReferenceType,
 };
 
-use crate::util::load_next_js_template;
+use crate::{
+    next_config::NextConfig,
+    next_manifests::MiddlewareMatcher,
+    path_to_regexp::path_to_regexp,
+    util::{load_next_js_template, MiddlewareMatcherKind},
+};
 
 #[turbo_tasks::function]
 pub async fn middleware_files(page_extensions: Vc<Vec<String>>) -> Result<Vc<Vec<String>>> {
@@ -56,3 +61,80 @@ pub async fn get_middleware_module(
 
     Ok(module)
 }
+
+/// Compiles the `config.matcher` of a middleware into the matchers of the
+/// middleware manifest, like Next.js' `getMiddlewareMatchers` does. Without
+/// matchers, the middleware runs on every request.
+pub fn get_middleware_matchers(
+    matchers: Option<&[MiddlewareMatcherKind]>,
+    next_config: &NextConfig,
+) -> Result<Vec<MiddlewareMatcher>> {
+    let Some(matchers) = matchers else {
+        return Ok(vec![MiddlewareMatcher {
+            regexp: Some("^/.*$".to_string()),
+            locale: true,
+            original_source: "/:path*".to_string(),
+            ..Default::default()
+        }]);
+    };
+
+    matchers
+        .iter()
+        .map(|matcher| {
+            let (original_source, locale, has, missing) = match matcher {
+                MiddlewareMatcherKind::Str(source) => (source, true, None, None),
+                MiddlewareMatcherKind::Matcher(matcher) => (
+                    &matcher.source,
+                    matcher.locale.unwrap_or(true),
+                    matcher.has.clone(),
+                    matcher.missing.clone(),
+                ),
+            };
+            if !original_source.starts_with('/') {
+                bail!("middleware matcher source {original_source:?} must start with \"/\"");
+            }
+
+            let is_root = original_source == "/";
+            let mut source = original_source.clone();
+            if locale
+                && next_config
+                    .i18n
+                    .as_ref()
+                    .map_or(false, |i18n| !i18n.locales.is_empty())
+            {
+                source = format!(
+                    "/:nextInternalLocale((?!_next/)[^/.]{{1,}}){}",
+                    if is_root { "" } else { &source }
+                );
+            }
+            let suffix = if is_root {
+                format!(
+                    "({}/?index|/?index\\.json)?",
+                    if next_config.i18n.is_some() {
+                        "|\\.json|"
+                    } else {
+                        ""
+                    }
+                )
+            } else {
+                "(.json)?".to_string()
+            };
+            source = format!("/:nextData(_next/data/[^/]{{1,}})?{source}{suffix}");
+            if let Some(base_path) = &next_config.base_path {
+                source = format!("{base_path}{source}");
+            }
+
+            Ok(MiddlewareMatcher {
+                regexp: Some(
+                    path_to_regexp(&source).with_context(|| {
+                        format!("invalid middleware matcher {original_source:?}")
+                    })?,
+                ),
+                locale,
+                has: has.map(|has| has.into_iter().map(Into::into).collect()),
+                missing: missing.map(|missing| missing.into_iter().map(Into::into).collect()),
+                original_source: original_source.clone(),
+            })
+        })
+        .collect()
+}
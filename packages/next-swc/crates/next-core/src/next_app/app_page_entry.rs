@@ -22,7 +22,7 @@ use turbopack_binding::{
 use super::app_entry::AppEntry;
 use crate::{
     app_structure::LoaderTree,
-    loader_tree::LoaderTreeModule,
+    loader_tree::{LoaderTreeModule, GLOBAL_ERROR},
     mode::NextMode,
     next_app::{AppPage, AppPath},
     next_config::NextConfig,
@@ -41,6 +41,7 @@ pub async fn get_app_page_entry(
     page: AppPage,
     project_root: Vc<FileSystemPath>,
     next_config: Vc<NextConfig>,
+    mode: NextMode,
 ) -> Result<Vc<AppEntry>> {
     let config = parse_segment_config_from_loader_tree(loader_tree);
     let is_edge = matches!(config.await?.runtime, Some(NextRuntime::Edge));
@@ -78,6 +79,12 @@ pub async fn get_app_page_entry(
     let original_name = page.to_string();
     let pathname = AppPath::from(page.clone()).to_string();
 
+    let global_error = if inner_assets.contains_key(GLOBAL_ERROR) {
+        GLOBAL_ERROR.to_string()
+    } else {
+        "next/dist/client/components/error-boundary".to_string()
+    };
+
     // Load the file from the next.js codebase.
     let source = load_next_js_template(
         "app-page.js",
@@ -86,8 +93,7 @@ pub async fn get_app_page_entry(
             "VAR_DEFINITION_PAGE" => page.to_string(),
             "VAR_DEFINITION_PATHNAME" => pathname.clone(),
             "VAR_ORIGINAL_PATHNAME" => original_name.clone(),
-            // TODO(alexkirsz) Support custom global error.
-            "VAR_MODULE_GLOBAL_ERROR" => "next/dist/client/components/error-boundary".to_string(),
+            "VAR_MODULE_GLOBAL_ERROR" => global_error,
         },
         indexmap! {
             "tree" => loader_tree_code,
@@ -120,6 +126,7 @@ pub async fn get_app_page_entry(
             rsc_entry,
             page,
             next_config,
+            mode,
         );
     };
 
@@ -145,6 +152,7 @@ async fn wrap_edge_page(
     entry: Vc<Box<dyn Module>>,
     page: AppPage,
     next_config: Vc<NextConfig>,
+    mode: NextMode,
 ) -> Result<Vc<Box<dyn Module>>> {
     const INNER: &str = "INNER_PAGE_ENTRY";
 
@@ -152,7 +160,7 @@ async fn wrap_edge_page(
 
     // TODO(WEB-1824): add build support
     let build_id = "development";
-    let dev = true;
+    let dev = mode == NextMode::Development;
 
     // TODO(timneutkens): remove this
     let is_server_component = true;
@@ -164,7 +172,7 @@ async fn wrap_edge_page(
             .experimental
             .sri
             .as_ref()
-            .map(|sri| sri.algorithm.as_ref())
+            .and_then(|sri| sri.algorithm)
             .is_some();
 
     let source = load_next_js_template(
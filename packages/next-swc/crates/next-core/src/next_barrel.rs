@@ -0,0 +1,392 @@
+//! Support for `experimental.optimizePackageImports`.
+//!
+//! Named imports of the optimized packages are rewritten by the
+//! `named_import_transform` to `__barrel_optimize__?names=a,b!=!package`.
+//! [NextBarrelOptimizeReplacer] resolves these requests to a module which
+//! re-exports `a` and `b` from the files which define them, so the rest of the
+//! package's barrel file doesn't have to be compiled.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use next_custom_transforms::transforms::optimize_barrel::{optimize_barrel, Config};
+use turbo_tasks::{Value, Vc};
+use turbopack_binding::{
+    swc::core::{
+        common::GLOBALS,
+        ecma::{
+            ast::{Decl, Expr, Lit, ModuleDecl, ModuleItem, Program},
+            visit::FoldWith,
+        },
+    },
+    turbo::{
+        tasks_fs::{File, FileContent, FileSystemPath},
+        tasks_hash::hash_xxh3_hash64,
+    },
+    turbopack::{
+        core::{
+            asset::AssetContent,
+            reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
+            resolve::{
+                options::{ImportMapResult, ImportMapping, ImportMappingReplacement},
+                parse::Request,
+                pattern::Pattern,
+                resolve, ResolveResult,
+            },
+            source::Source,
+            virtual_source::VirtualSource,
+        },
+        ecmascript::{
+            parse::{parse, ParseResult},
+            utils::StringifyJs,
+            EcmascriptInputTransforms, EcmascriptModuleAssetType,
+        },
+    },
+};
+
+use crate::next_import_map::package_lookup_resolve_options;
+
+/// The prefix of the `optimize_barrel` transform for wildcard exports, whose
+/// names are only known once they're imported.
+const WILDCARD_PREFIX: &str = "__barrel_optimize__?names=__PLACEHOLDER__!=!";
+
+#[turbo_tasks::value(shared)]
+pub(crate) struct NextBarrelOptimizeReplacer {
+    project_path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl NextBarrelOptimizeReplacer {
+    #[turbo_tasks::function]
+    pub fn new(project_path: Vc<FileSystemPath>) -> Vc<Self> {
+        Self::cell(NextBarrelOptimizeReplacer { project_path })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for NextBarrelOptimizeReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Ignore.into()
+    }
+
+    /// Resolves `__barrel_optimize__?names=a,b!=!package` to a module which
+    /// only re-exports `a` and `b` from `package`.
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        context: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        let Request::Module { query, .. } = &*request.await? else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let query = query.await?;
+        let Some((names, package)) = query
+            .strip_prefix("?names=")
+            .and_then(|query| query.split_once("!=!"))
+        else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+
+        let barrel = resolve(
+            context,
+            Value::new(ReferenceType::EcmaScriptModules(
+                EcmaScriptModulesReferenceSubType::Import,
+            )),
+            Request::parse(Value::new(Pattern::Constant(package.to_string()))),
+            package_lookup_resolve_options(self.project_path),
+        )
+        .first_source()
+        .await?;
+        let Some(barrel) = *barrel else {
+            return Ok(ImportMapResult::Result(ResolveResult::unresolveable().into()).into());
+        };
+
+        let Some(exports) = &*barrel_exports(barrel).await? else {
+            // Not a barrel file, so there's nothing to skip.
+            return Ok(ImportMapResult::Result(ResolveResult::source(barrel).into()).into());
+        };
+
+        let mut code = String::new();
+        if exports
+            .directives
+            .iter()
+            .any(|directive| directive == "use client")
+        {
+            writeln!(code, "\"use client\";")?;
+        }
+
+        let mut missing_names = vec![];
+        for name in names.split(',').filter(|name| !name.is_empty()) {
+            let Some((_, module, orig)) = exports
+                .exports
+                .iter()
+                .find(|(exported, ..)| exported == name)
+            else {
+                missing_names.push(name);
+                continue;
+            };
+            let module = StringifyJs(module);
+            if orig == "*" {
+                writeln!(code, "export * as {name} from {module};")?;
+            } else if orig == name {
+                writeln!(code, "export {{ {name} }} from {module};")?;
+            } else {
+                writeln!(code, "export {{ {orig} as {name} }} from {module};")?;
+            }
+        }
+
+        // The remaining names can only come from the wildcard exports, which are
+        // optimized in turn.
+        if !missing_names.is_empty() {
+            for module in &exports.wildcard_exports {
+                let request = format!(
+                    "__barrel_optimize__?names={}!=!{module}",
+                    missing_names.join(",")
+                );
+                writeln!(code, "export * from {};", StringifyJs(&request))?;
+            }
+        }
+
+        // Next to the barrel file, so that the re-exports resolve like its own.
+        let barrel_path = barrel.ident().path();
+        let file_stem = barrel_path.file_stem().await?;
+        let path = barrel_path.parent().join(format!(
+            "{}.__barrel_optimize__.{:x}.js",
+            file_stem.as_deref().unwrap_or("index"),
+            hash_xxh3_hash64(names)
+        ));
+        let source = VirtualSource::new(
+            path,
+            AssetContent::file(FileContent::Content(File::from(code)).into()),
+        );
+
+        Ok(ImportMapResult::Result(ResolveResult::source(Vc::upcast(source)).into()).into())
+    }
+}
+
+/// The exports of a barrel file, as found by the `optimize_barrel` transform.
+#[turbo_tasks::value]
+struct BarrelExports {
+    /// `(exported name, module, imported name)`.
+    exports: Vec<(String, String, String)>,
+    /// The modules of `export * from`.
+    wildcard_exports: Vec<String>,
+    directives: Vec<String>,
+}
+
+#[turbo_tasks::value(transparent)]
+struct OptionBarrelExports(Option<BarrelExports>);
+
+/// Returns `None` if `source` isn't a barrel file, i.e. if it contains more
+/// than imports, re-exports and directives.
+#[turbo_tasks::function]
+async fn barrel_exports(source: Vc<Box<dyn Source>>) -> Result<Vc<OptionBarrelExports>> {
+    let path = source.ident().path().await?;
+    let ty = if path.path.ends_with(".ts") || path.path.ends_with(".mts") {
+        EcmascriptModuleAssetType::Typescript {
+            tsx: false,
+            analyze_types: false,
+        }
+    } else if path.path.ends_with(".tsx") {
+        EcmascriptModuleAssetType::Typescript {
+            tsx: true,
+            analyze_types: false,
+        }
+    } else {
+        EcmascriptModuleAssetType::Ecmascript
+    };
+
+    let ParseResult::Ok {
+        program: program @ Program::Module(_),
+        globals,
+        ..
+    } = &*parse(source, Value::new(ty), EcmascriptInputTransforms::empty()).await?
+    else {
+        return Ok(Vc::cell(None));
+    };
+
+    let Program::Module(module) = GLOBALS.set(globals, || {
+        program
+            .clone()
+            .fold_with(&mut optimize_barrel(Config { wildcard: false }))
+    }) else {
+        return Ok(Vc::cell(None));
+    };
+
+    let mut exports = None;
+    let mut wildcard_exports = vec![];
+    let mut directives = vec![];
+    for item in &module.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+                let Decl::Var(var) = &export_decl.decl else {
+                    continue;
+                };
+                for decl in &var.decls {
+                    let (Some(ident), Some(Expr::Lit(Lit::Str(value)))) =
+                        (decl.name.as_ident(), decl.init.as_deref())
+                    else {
+                        continue;
+                    };
+                    match &*ident.sym {
+                        "__next_private_export_map__" => {
+                            exports = Some(serde_json::from_str(&value.value)?);
+                        }
+                        "__next_private_directive_list__" => {
+                            directives = serde_json::from_str(&value.value)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportAll(export_all)) => {
+                let src = &export_all.src.value;
+                wildcard_exports.push(src.strip_prefix(WILDCARD_PREFIX).unwrap_or(src).to_string());
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Vc::cell(exports.map(|exports| BarrelExports {
+        exports,
+        wildcard_exports,
+        directives,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use anyhow::{bail, Result};
+    use turbo_tasks::{TurboTasks, Value, Vc};
+    use turbopack_binding::{
+        turbo::{
+            tasks_fs::{DiskFileSystem, FileContent, FileSystem, FileSystemPath},
+            tasks_memory::MemoryBackend,
+        },
+        turbopack::core::{
+            asset::Asset,
+            resolve::{
+                options::{ImportMapResult, ImportMappingReplacement},
+                parse::Request,
+                pattern::Pattern,
+            },
+        },
+    };
+
+    use super::NextBarrelOptimizeReplacer;
+
+    /// Writes `files` to a new directory in the temp dir.
+    fn write_project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("next-barrel-{name}-{}", std::process::id()));
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    /// Removes a directory once the test is done with it, even if it fails.
+    struct RemoveDirOnDrop(PathBuf);
+
+    impl Drop for RemoveDirOnDrop {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// The code of the module `request` is resolved to from `context`.
+    async fn optimized_module(
+        project_path: Vc<FileSystemPath>,
+        context: Vc<FileSystemPath>,
+        request: &str,
+    ) -> Result<String> {
+        let result = NextBarrelOptimizeReplacer::new(project_path).result(
+            context,
+            Request::parse(Value::new(Pattern::Constant(request.to_string()))),
+        );
+        let ImportMapResult::Result(result) = &*result.await? else {
+            bail!("expected {request} to be mapped");
+        };
+        let Some(source) = *result.first_source().await? else {
+            bail!("expected {request} to resolve to a source");
+        };
+        let FileContent::Content(file) = &*source.content().file_content().await? else {
+            bail!("expected {request} to resolve to a file");
+        };
+        Ok(file.content().to_str()?.into_owned())
+    }
+
+    #[tokio::test]
+    async fn reexports_the_imported_names_from_their_modules() {
+        crate::register();
+        let root = write_project(
+            "reexports",
+            &[
+                (
+                    "node_modules/pkg/package.json",
+                    r#"{ "name": "pkg", "main": "index.js" }"#,
+                ),
+                (
+                    "node_modules/pkg/index.js",
+                    "'use client'\nexport { a } from './a'\nexport { a as renamed } from \
+                     './a'\nexport * as ns from './ns'\nexport * from './more'\n",
+                ),
+                ("node_modules/pkg/more.js", "export { c } from './c'\n"),
+                (
+                    "node_modules/plain/package.json",
+                    r#"{ "name": "plain", "main": "index.js" }"#,
+                ),
+                ("node_modules/plain/index.js", "export const a = 1\n"),
+            ],
+        );
+        let _remove_project = RemoveDirOnDrop(root.clone());
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        tt.run_once(async move {
+            let project_path =
+                DiskFileSystem::new("project".to_string(), root.to_str().unwrap().to_string())
+                    .root();
+
+            assert_eq!(
+                optimized_module(
+                    project_path,
+                    project_path,
+                    "__barrel_optimize__?names=a,renamed,ns,c!=!pkg"
+                )
+                .await?,
+                "\"use client\";\nexport { a } from \"./a\";\nexport { a as renamed } from \
+                 \"./a\";\nexport * as ns from \"./ns\";\nexport * from \
+                 \"__barrel_optimize__?names=c!=!./more\";\n"
+            );
+
+            // The wildcard exports are optimized in turn, next to the barrel file.
+            assert_eq!(
+                optimized_module(
+                    project_path,
+                    project_path.join("node_modules/pkg".to_string()),
+                    "__barrel_optimize__?names=c!=!./more"
+                )
+                .await?,
+                "export { c } from \"./c\";\n"
+            );
+
+            // Packages without a barrel file are left as they are.
+            assert_eq!(
+                optimized_module(
+                    project_path,
+                    project_path,
+                    "__barrel_optimize__?names=a!=!plain"
+                )
+                .await?,
+                "export const a = 1\n"
+            );
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -14,6 +14,7 @@ use crate::{
         next_cjs_optimizer::get_next_cjs_optimizer_rule,
         next_disallow_re_export_all_in_page::get_next_disallow_export_all_in_page_rule,
         next_page_config::get_next_page_config_rule, next_pure::get_next_pure_rule,
+        optimize_package_imports::get_next_optimize_package_imports_rule,
         server_actions::ActionsTransform,
     },
 };
@@ -36,6 +37,13 @@ pub async fn get_next_client_transforms_rules(
     }
 
     let mdx_rs = *next_config.mdx_rs().await?;
+    let optimize_package_imports = next_config.optimize_package_imports().await?;
+    if !optimize_package_imports.is_empty() {
+        rules.push(get_next_optimize_package_imports_rule(
+            optimize_package_imports.clone_value(),
+            mdx_rs,
+        ));
+    }
     rules.push(get_next_font_transform_rule(mdx_rs));
 
     let pages_dir = match context_ty {
@@ -2,7 +2,7 @@ use anyhow::{Context, Result};
 use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value as JsonValue;
-use turbo_tasks::{trace::TraceRawVcs, Completion, Value, Vc};
+use turbo_tasks::{trace::TraceRawVcs, Completion, TaskInput, Value, Vc};
 use turbo_tasks_fs::json::parse_json_with_source_context;
 use turbopack_binding::{
     turbo::{tasks_env::EnvMap, tasks_fs::FileSystemPath},
@@ -60,15 +60,15 @@ struct NextConfigAndCustomRoutes {
 #[serde(rename_all = "camelCase")]
 struct CustomRoutesRaw {
     rewrites: Rewrites,
-
-    // unsupported
-    headers: Vec<Header>,
-    redirects: Vec<Redirect>,
+    headers: Headers,
+    redirects: Redirects,
 }
 
 #[turbo_tasks::value]
 struct CustomRoutes {
     rewrites: Vc<Rewrites>,
+    headers: Vc<Headers>,
+    redirects: Vc<Redirects>,
 }
 
 #[turbo_tasks::value(serialization = "custom", eq = "manual")]
@@ -108,6 +108,10 @@ pub struct NextConfig {
     ///
     #[serde(rename = "_originalRedirects")]
     pub original_redirects: Option<Vec<Redirect>>,
+    /// The rewrites as configured, before Next.js adds the base path and
+    /// locales to them.
+    #[serde(rename = "_originalRewrites")]
+    pub original_rewrites: Option<Rewrites>,
 
     // Partially supported
     pub compiler: Option<CompilerConfig>,
@@ -195,7 +199,9 @@ struct HttpAgentConfig {
 pub struct DomainLocale {
     pub default_locale: String,
     pub domain: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub http: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub locales: Option<Vec<String>>,
 }
 
@@ -203,11 +209,30 @@ pub struct DomainLocale {
 #[serde(rename_all = "camelCase")]
 pub struct I18NConfig {
     pub default_locale: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub domains: Option<Vec<DomainLocale>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub locale_detection: Option<bool>,
     pub locales: Vec<String>,
 }
 
+impl I18NConfig {
+    /// The locale-prefixed pathnames a page at `pathname` is also served at,
+    /// one for each locale.
+    pub fn localized_pathnames<'a>(
+        &'a self,
+        pathname: &'a str,
+    ) -> impl Iterator<Item = String> + 'a {
+        self.locales.iter().map(move |locale| {
+            if pathname == "/" {
+                format!("/{locale}")
+            } else {
+                format!("/{locale}{pathname}")
+            }
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "kebab-case")]
 pub enum OutputType {
@@ -215,7 +240,7 @@ pub enum OutputType {
     Export,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum RouteHas {
     Header {
@@ -309,6 +334,14 @@ pub struct Rewrites {
     pub fallback: Vec<Rewrite>,
 }
 
+#[turbo_tasks::value(transparent, eq = "manual")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Headers(Vec<Header>);
+
+#[turbo_tasks::value(transparent, eq = "manual")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Redirects(Vec<Redirect>);
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
 pub struct TypeScriptConfig {
@@ -328,10 +361,10 @@ pub struct ImageConfig {
     pub loader_file: Option<String>,
     pub domains: Vec<String>,
     pub disable_static_images: bool,
-    #[serde(rename(deserialize = "minimumCacheTTL"))]
+    #[serde(rename = "minimumCacheTTL")]
     pub minimum_cache_ttl: u32,
     pub formats: Vec<ImageFormat>,
-    #[serde(rename(deserialize = "dangerouslyAllowSVG"))]
+    #[serde(rename = "dangerouslyAllowSVG")]
     pub dangerously_allow_svg: bool,
     pub content_security_policy: String,
     pub remote_patterns: Vec<RemotePattern>,
@@ -377,7 +410,9 @@ pub enum ImageLoader {
     Custom,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, TaskInput, TraceRawVcs,
+)]
 pub enum ImageFormat {
     #[serde(rename = "image/webp")]
     Webp,
@@ -468,6 +503,24 @@ pub struct ExperimentalConfig {
     pub web_vitals_attribution: Option<Vec<String>>,
     pub server_actions: Option<ServerActionsOrLegacyBool>,
     pub sri: Option<SubResourceIntegrity>,
+    /// Whether ES modules in node_modules are externalized on the server.
+    /// Defaults to `true`.
+    pub esm_externals: Option<EsmExternals>,
+    /// Globs of files to leave out of the traced server files.
+    pub output_file_tracing_ignores: Option<Vec<String>>,
+    /// Globs of files, relative to the project, to add to the traced server
+    /// files of the routes matching the key glob.
+    pub output_file_tracing_includes: Option<IndexMap<String, Vec<String>>>,
+    /// The directory server files are traced relative to. Defaults to the
+    /// root of the workspace.
+    pub output_file_tracing_root: Option<String>,
+    /// Automatically apply the "modularize_imports" optimization to imports of
+    /// the specified packages.
+    optimize_package_imports: Option<Vec<String>>,
+    /// Generate Route types and enable type checking for Link and Router.push,
+    /// etc. This option requires `appDir` to be enabled first.
+    /// @see https://nextjs.org/docs/app/api-reference/next-config-js/typedRoutes
+    typed_routes: Option<bool>,
 
     // ---
     // UNSUPPORTED
@@ -481,7 +534,6 @@ pub struct ExperimentalConfig {
     cra_compat: Option<bool>,
     disable_optimized_loading: Option<bool>,
     disable_postcss_preset_env: Option<bool>,
-    esm_externals: Option<serde_json::Value>,
     extension_alias: Option<serde_json::Value>,
     external_dir: Option<bool>,
     /// If set to `false`, webpack won't fall back to polyfill Node.js modules
@@ -498,12 +550,6 @@ pub struct ExperimentalConfig {
     memory_based_workers_count: Option<bool>,
     /// Optimize React APIs for server builds.
     optimize_server_react: Option<bool>,
-    /// Automatically apply the "modularize_imports" optimization to imports of
-    /// the specified packages.
-    optimize_package_imports: Option<Vec<String>>,
-    output_file_tracing_ignores: Option<Vec<String>>,
-    output_file_tracing_includes: Option<serde_json::Value>,
-    output_file_tracing_root: Option<String>,
     /// Using this feature will enable the `react@experimental` for the `app`
     /// directory.
     ppr: Option<bool>,
@@ -517,10 +563,6 @@ pub struct ExperimentalConfig {
     swc_trace_profiling: Option<bool>,
     /// @internal Used by the Next.js internals only.
     trust_host_header: Option<bool>,
-    /// Generate Route types and enable type checking for Link and Router.push,
-    /// etc. This option requires `appDir` to be enabled first.
-    /// @see https://nextjs.org/docs/app/api-reference/next-config-js/typedRoutes
-    typed_routes: Option<bool>,
     url_imports: Option<serde_json::Value>,
     /// This option is to enable running the Webpack build in a worker thread
     /// (doesn't apply to Turbopack).
@@ -533,7 +575,16 @@ pub struct ExperimentalConfig {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
 pub struct SubResourceIntegrity {
-    pub algorithm: Option<String>,
+    pub algorithm: Option<SriAlgorithm>,
+}
+
+/// The hash algorithm of the `integrity` attributes of client scripts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "lowercase")]
+pub enum SriAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize, TraceRawVcs)]
@@ -554,6 +605,34 @@ pub struct ServerActions {
     pub body_size_limit: Option<SizeLimit>,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+pub enum EsmExternals {
+    Loose(EsmExternalsValue),
+    Bool(bool),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "kebab-case")]
+pub enum EsmExternalsValue {
+    Loose,
+}
+
+/// How ES modules are externalized on the server, see
+/// `experimental.esmExternals`.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Copy)]
+pub enum EsmExternalsMode {
+    /// ES modules are always bundled.
+    Disabled,
+    /// ES modules are externalized when they're imported. ES modules which are
+    /// required are bundled, and reported as an issue.
+    Enabled,
+    /// Like [EsmExternalsMode::Enabled], but ES modules which are required are
+    /// externalized as well.
+    Loose,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(untagged)]
 pub enum SizeLimit {
@@ -655,6 +734,16 @@ impl NextConfig {
         ))
     }
 
+    #[turbo_tasks::function]
+    pub async fn esm_externals(self: Vc<Self>) -> Result<Vc<EsmExternalsMode>> {
+        Ok(match self.await?.experimental.esm_externals {
+            Some(EsmExternals::Bool(false)) => EsmExternalsMode::Disabled,
+            Some(EsmExternals::Bool(true)) | None => EsmExternalsMode::Enabled,
+            Some(EsmExternals::Loose(_)) => EsmExternalsMode::Loose,
+        }
+        .cell())
+    }
+
     #[turbo_tasks::function]
     pub async fn env(self: Vc<Self>) -> Result<Vc<EnvMap>> {
         // The value expected for env is Record<String, String>, but config itself
@@ -758,6 +847,19 @@ impl NextConfig {
         Ok(alias_map.cell())
     }
 
+    /// The packages whose imports are optimized, see
+    /// `experimental.optimizePackageImports`.
+    #[turbo_tasks::function]
+    pub async fn optimize_package_imports(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .optimize_package_imports
+                .clone()
+                .unwrap_or_default(),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn mdx_rs(self: Vc<Self>) -> Result<Vc<bool>> {
         Ok(Vc::cell(self.await?.experimental.mdx_rs.unwrap_or(false)))
@@ -818,12 +920,24 @@ impl NextConfig {
         Ok(Vc::cell(self.await?.experimental.taint.unwrap_or(false)))
     }
 
+    #[turbo_tasks::function]
+    pub async fn typed_routes(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?.experimental.typed_routes.unwrap_or(false),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn use_lightningcss(self: Vc<Self>) -> Result<Vc<bool>> {
         Ok(Vc::cell(
             self.await?.experimental.use_lightningcss.unwrap_or(false),
         ))
     }
+
+    #[turbo_tasks::function]
+    pub async fn is_export(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(self.await?.output == Some(OutputType::Export)))
+    }
 }
 
 fn next_configs() -> Vc<Vec<String>> {
@@ -851,6 +965,24 @@ pub async fn load_rewrites(execution_context: Vc<ExecutionContext>) -> Result<Vc
         .rewrites)
 }
 
+#[turbo_tasks::function]
+pub async fn load_headers(execution_context: Vc<ExecutionContext>) -> Result<Vc<Headers>> {
+    Ok(load_config_and_custom_routes(execution_context)
+        .await?
+        .custom_routes
+        .await?
+        .headers)
+}
+
+#[turbo_tasks::function]
+pub async fn load_redirects(execution_context: Vc<ExecutionContext>) -> Result<Vc<Redirects>> {
+    Ok(load_config_and_custom_routes(execution_context)
+        .await?
+        .custom_routes
+        .await?
+        .redirects)
+}
+
 #[turbo_tasks::function]
 async fn load_config_and_custom_routes(
     execution_context: Vc<ExecutionContext>,
@@ -941,6 +1073,8 @@ async fn load_next_config_and_custom_routes_internal(
             config: NextConfig::default().cell(),
             custom_routes: CustomRoutes {
                 rewrites: Rewrites::default().cell(),
+                headers: Headers::default().cell(),
+                redirects: Redirects::default().cell(),
             }
             .cell(),
         }
@@ -974,6 +1108,8 @@ async fn load_next_config_and_custom_routes_internal(
         config: next_config_and_custom_routes.config.cell(),
         custom_routes: CustomRoutes {
             rewrites: next_config_and_custom_routes.custom_routes.rewrites.cell(),
+            headers: next_config_and_custom_routes.custom_routes.headers.cell(),
+            redirects: next_config_and_custom_routes.custom_routes.redirects.cell(),
         }
         .cell(),
     }
@@ -1058,3 +1194,26 @@ impl Issue for OutdatedConfigIssue {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::I18NConfig;
+
+    #[test]
+    fn localizes_pathnames() {
+        let i18n = I18NConfig {
+            default_locale: "en".to_string(),
+            domains: None,
+            locale_detection: None,
+            locales: vec!["en".to_string(), "fr".to_string()],
+        };
+        assert_eq!(
+            i18n.localized_pathnames("/").collect::<Vec<_>>(),
+            ["/en", "/fr"]
+        );
+        assert_eq!(
+            i18n.localized_pathnames("/blog/[slug]").collect::<Vec<_>>(),
+            ["/en/blog/[slug]", "/fr/blog/[slug]"]
+        );
+    }
+}
@@ -3,6 +3,7 @@
 
 use std::collections::HashMap;
 
+use indexmap::IndexMap;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
@@ -27,7 +28,7 @@ pub struct RouteRegex {
 pub struct NamedRouteRegex {
     pub regex: RouteRegex,
     pub named_regex: String,
-    pub route_keys: HashMap<String, String>,
+    pub route_keys: IndexMap<String, String>,
 }
 
 #[derive(Debug)]
@@ -129,6 +130,13 @@ fn get_parametrized_route(route: &str) -> (String, HashMap<String, Group>) {
     (parameterized_route, groups)
 }
 
+/// Returns true if the route has a dynamic segment, e.g. `/blog/[slug]`.
+pub fn is_dynamic_route(route: &str) -> bool {
+    route
+        .split('/')
+        .any(|segment| segment.starts_with('[') && segment.ends_with(']'))
+}
+
 /// From a normalized route this function generates a regular expression and
 /// a corresponding groups object intended to be used to store matching groups
 /// from the regular expression.
@@ -163,7 +171,7 @@ fn build_get_safe_route_key() -> impl FnMut() -> String {
 fn get_safe_key_from_segment(
     get_safe_route_key: &mut impl FnMut() -> String,
     segment: &str,
-    route_keys: &mut HashMap<String, String>,
+    route_keys: &mut IndexMap<String, String>,
     key_prefix: Option<&'static str>,
 ) -> String {
     let ParsedParameter {
@@ -207,10 +215,10 @@ fn get_safe_key_from_segment(
 fn get_named_parametrized_route(
     route: &str,
     prefix_route_keys: bool,
-) -> (String, HashMap<String, String>) {
+) -> (String, IndexMap<String, String>) {
     let segments: Vec<&str> = remove_trailing_slash(route)[1..].split('/').collect();
     let get_safe_route_key = &mut build_get_safe_route_key();
-    let mut route_keys: HashMap<String, String> = HashMap::new();
+    let mut route_keys: IndexMap<String, String> = IndexMap::new();
     let parameterized_route = segments
         .iter()
         .map(|segment| {
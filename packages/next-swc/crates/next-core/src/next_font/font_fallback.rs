@@ -7,16 +7,18 @@ use turbopack_binding::turbo::tasks::trace::TraceRawVcs;
 pub(crate) struct DefaultFallbackFont {
     pub name: String,
     pub capsize_key: String,
-    pub az_avg_width: f64,
+    /// The capsize `xWidthAvg` metric of the font.
+    pub x_width_avg: f64,
     pub units_per_em: u32,
 }
 
 // From https://github.com/vercel/next.js/blob/a3893bf69c83fb08e88c87bf8a21d987a0448c8e/packages/font/src/utils.ts#L4
+// and https://github.com/seek-oss/capsize/blob/42d6dc39d58247bc6b9e013a4b1c4463bf287dca/packages/metrics/src/entireMetricsCollection.json
 pub(crate) static DEFAULT_SANS_SERIF_FONT: Lazy<DefaultFallbackFont> =
     Lazy::new(|| DefaultFallbackFont {
         name: "Arial".to_owned(),
         capsize_key: "arial".to_owned(),
-        az_avg_width: 934.5116279069767,
+        x_width_avg: 904.0,
         units_per_em: 2048,
     });
 
@@ -24,7 +26,7 @@ pub(crate) static DEFAULT_SERIF_FONT: Lazy<DefaultFallbackFont> =
     Lazy::new(|| DefaultFallbackFont {
         name: "Times New Roman".to_owned(),
         capsize_key: "timesNewRoman".to_owned(),
-        az_avg_width: 854.3953488372093,
+        x_width_avg: 819.0,
         units_per_em: 2048,
     });
 
@@ -79,7 +81,7 @@ impl FontFallbacks {
 /// An adjustment to be made to a fallback font to approximate the geometry of
 /// the main webfont. Rendered as e.g. `ascent-override: 56.8%;` in the
 /// stylesheet
-#[derive(Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 pub(crate) struct FontAdjustment {
     pub ascent: f64,
     pub descent: f64,
@@ -16,6 +16,7 @@ use turbopack_binding::{
             json::parse_json_with_source_context, DiskFileSystem, File, FileContent, FileSystem,
             FileSystemPath,
         },
+        tasks_hash::hash_xxh3_hash64,
     },
     turbopack::{
         core::{
@@ -39,7 +40,7 @@ use turbopack_binding::{
 use self::{
     font_fallback::get_font_fallback,
     options::{options_from_request, FontDataEntry, FontWeights, NextFontGoogleOptions},
-    stylesheet::build_stylesheet,
+    stylesheet::{build_offline_stylesheet, build_stylesheet},
     util::{get_font_axes, get_stylesheet_url},
 };
 use super::{
@@ -49,7 +50,8 @@ use super::{
     },
 };
 use crate::{
-    embed_js::next_js_file_path, next_app::metadata::split_extension, util::load_next_js_templateon,
+    embed_js::next_js_file_path, mode::NextMode, next_app::metadata::split_extension,
+    util::load_next_js_templateon,
 };
 
 pub mod font_fallback;
@@ -163,7 +165,9 @@ impl ImportMappingReplacement for NextFontGoogleReplacer {
 #[turbo_tasks::value(shared)]
 pub struct NextFontGoogleCssModuleReplacer {
     project_path: Vc<FileSystemPath>,
+    mode: NextMode,
     execution_context: Vc<ExecutionContext>,
+    cache_dir: Vc<FileSystemPath>,
 }
 
 #[turbo_tasks::value_impl]
@@ -171,11 +175,15 @@ impl NextFontGoogleCssModuleReplacer {
     #[turbo_tasks::function]
     pub fn new(
         project_path: Vc<FileSystemPath>,
+        mode: NextMode,
         execution_context: Vc<ExecutionContext>,
+        cache_dir: Vc<FileSystemPath>,
     ) -> Vc<Self> {
         Self::cell(NextFontGoogleCssModuleReplacer {
             project_path,
+            mode,
             execution_context,
+            cache_dir,
         })
     }
 }
@@ -231,7 +239,15 @@ impl ImportMappingReplacement for NextFontGoogleCssModuleReplacer {
         let stylesheet_str = mocked_responses_path
             .as_ref()
             .map_or_else(
-                || fetch_real_stylesheet(stylesheet_url, css_virtual_path).boxed(),
+                || {
+                    fetch_real_stylesheet(
+                        stylesheet_url,
+                        css_virtual_path,
+                        self.cache_dir,
+                        self.mode,
+                    )
+                    .boxed()
+                },
                 |p| get_mock_stylesheet(stylesheet_url, p, self.execution_context).boxed(),
             )
             .await?;
@@ -249,7 +265,11 @@ impl ImportMappingReplacement for NextFontGoogleCssModuleReplacer {
                 .await?
                 .clone_value(),
             ),
-            None => None,
+            // Google Fonts couldn't be reached during development. Declare the
+            // font as its fallback, so its class names keep working offline.
+            None => build_offline_stylesheet(scoped_font_family, font_fallback)
+                .await?
+                .clone_value(),
         };
 
         let css_asset = VirtualSource::new(
@@ -282,13 +302,23 @@ struct NextFontGoogleFontFileOptions {
 #[turbo_tasks::value(shared)]
 pub struct NextFontGoogleFontFileReplacer {
     project_path: Vc<FileSystemPath>,
+    mode: NextMode,
+    cache_dir: Vc<FileSystemPath>,
 }
 
 #[turbo_tasks::value_impl]
 impl NextFontGoogleFontFileReplacer {
     #[turbo_tasks::function]
-    pub fn new(project_path: Vc<FileSystemPath>) -> Vc<Self> {
-        Self::cell(NextFontGoogleFontFileReplacer { project_path })
+    pub fn new(
+        project_path: Vc<FileSystemPath>,
+        mode: NextMode,
+        cache_dir: Vc<FileSystemPath>,
+    ) -> Vc<Self> {
+        Self::cell(NextFontGoogleFontFileReplacer {
+            project_path,
+            mode,
+            cache_dir,
+        })
     }
 }
 
@@ -341,7 +371,10 @@ impl ImportMappingReplacement for NextFontGoogleFontFileReplacer {
 
         // doesn't seem ideal to download the font into a string, but probably doesn't
         // really matter either.
-        let Some(font) = fetch_from_google_fonts(Vc::cell(url), font_virtual_path).await? else {
+        let Some(font) =
+            fetch_from_google_fonts(Vc::cell(url), font_virtual_path, self.cache_dir, self.mode)
+                .await?
+        else {
             return Ok(ImportMapResult::Result(ResolveResult::unresolveable().into()).into());
         };
 
@@ -572,34 +605,60 @@ async fn font_file_options_from_query_map(
 async fn fetch_real_stylesheet(
     stylesheet_url: Vc<String>,
     css_virtual_path: Vc<FileSystemPath>,
+    cache_dir: Vc<FileSystemPath>,
+    mode: NextMode,
 ) -> Result<Option<Vc<String>>> {
-    let body = fetch_from_google_fonts(stylesheet_url, css_virtual_path).await?;
+    let body = fetch_from_google_fonts(stylesheet_url, css_virtual_path, cache_dir, mode).await?;
 
     Ok(body.map(|body| body.to_string()))
 }
 
+/// Fetches a stylesheet or font from Google Fonts. Successful responses are
+/// persisted in `cache_dir`, keyed by their url, and reused instead of hitting
+/// the network again.
 async fn fetch_from_google_fonts(
     url: Vc<String>,
     virtual_path: Vc<FileSystemPath>,
+    cache_dir: Vc<FileSystemPath>,
+    mode: NextMode,
 ) -> Result<Option<Vc<HttpResponseBody>>> {
+    let url_str = &*url.await?;
+    let cache_path = cache_dir.join(format!("{:016x}", hash_xxh3_hash64(url_str)));
+    if let FileContent::Content(file) = &*cache_path.read().await? {
+        return Ok(Some(
+            HttpResponseBody(file.content().to_bytes()?.into_owned()).cell(),
+        ));
+    }
+
     let result = fetch(url, Vc::cell(Some(USER_AGENT_FOR_GOOGLE_FONTS.to_owned()))).await?;
+    let err = match &*result {
+        Ok(r) => {
+            let body = r.await?.body;
+            cache_path
+                .write(FileContent::Content(File::from(body.await?.0.as_slice())).cell())
+                .await?;
+            return Ok(Some(body));
+        }
+        Err(err) => err,
+    };
 
-    Ok(match &*result {
-        Ok(r) => Some(r.await?.body),
-        Err(err) => {
+    match mode {
+        NextMode::Development => {
             // Inform the user of the failure to retreive the stylesheet / font, but don't
             // propagate this error. We don't want e.g. offline connections to prevent page
-            // renders during development. During production builds, however, this error
-            // should propagate.
-            //
-            // TODO(WEB-283): Use fallback in dev in this case
-            // TODO(WEB-293): Fail production builds (not dev) in this case
+            // renders during development.
             err.to_issue(IssueSeverity::Warning.into(), virtual_path)
                 .emit();
 
-            None
+            Ok(None)
         }
-    })
+        NextMode::Build => {
+            err.to_issue(IssueSeverity::Error.into(), virtual_path)
+                .emit();
+
+            bail!("Failed to fetch `{url_str}` from Google Fonts and no cached response exists")
+        }
+    }
 }
 
 async fn get_mock_stylesheet(
@@ -3,7 +3,7 @@ use turbo_tasks::Vc;
 
 use super::FontCssProperties;
 use crate::next_font::{
-    font_fallback::FontFallback,
+    font_fallback::{AutomaticFontFallback, FontFallback},
     stylesheet::{build_fallback_definition, build_font_class_rules},
 };
 
@@ -22,3 +22,26 @@ pub(super) async fn build_stylesheet(
     stylesheet.push_str(&build_font_class_rules(font_css_properties).await?);
     Ok(Vc::cell(stylesheet))
 }
+
+/// Builds a stylesheet for when Google Fonts can't be reached, declaring the
+/// scoped web font family as the local fallback font. Returns `None` when no
+/// automatic fallback font is available.
+#[turbo_tasks::function]
+pub(super) async fn build_offline_stylesheet(
+    scoped_font_family: Vc<String>,
+    font_fallback: Vc<FontFallback>,
+) -> Result<Vc<Option<String>>> {
+    let font_fallback = font_fallback.await?;
+    let FontFallback::Automatic(fallback) = &*font_fallback else {
+        return Ok(Vc::cell(None));
+    };
+
+    let offline_fallback = FontFallback::Automatic(AutomaticFontFallback {
+        scoped_font_family,
+        local_font_family: fallback.local_font_family,
+        adjustment: fallback.adjustment,
+    })
+    .cell();
+    let stylesheet = build_fallback_definition(Vc::cell(vec![offline_fallback])).await?;
+    Ok(Vc::cell(Some(stylesheet.clone_value())))
+}
@@ -1,5 +1,6 @@
 use allsorts::{
     font_data::{DynamicFontTableProvider, FontData},
+    tables::FontTableProvider,
     Font,
 };
 use anyhow::{bail, Context, Result};
@@ -18,9 +19,37 @@ use crate::next_font::{
     util::{get_scoped_font_family, FontFamilyType},
 };
 
-// From
-// https://github.com/vercel/next.js/blob/7457be0c74e64b4d0617943ed27f4d557cc916be/packages/font/src/local/get-fallback-metrics-from-font-file.ts#L34
-static AVG_CHARACTERS: &str = "aaabcdeeeefghiijklmnnoopqrrssttuvwxyz      ";
+// Character frequencies used by capsize to compute `xWidthAvg`, from
+// https://github.com/seek-oss/capsize/blob/42d6dc39d58247bc6b9e013a4b1c4463bf287dca/packages/unpack/src/index.ts#L7-L35
+static CHARACTER_WEIGHTINGS: [(char, f64); 27] = [
+    ('a', 0.0668),
+    ('b', 0.0122),
+    ('c', 0.0228),
+    ('d', 0.0348),
+    ('e', 0.1039),
+    ('f', 0.0182),
+    ('g', 0.0165),
+    ('h', 0.0499),
+    ('i', 0.057),
+    ('j', 0.0013),
+    ('k', 0.0063),
+    ('l', 0.0329),
+    ('m', 0.0197),
+    ('n', 0.0552),
+    ('o', 0.0614),
+    ('p', 0.0158),
+    ('q', 0.0008),
+    ('r', 0.049),
+    ('s', 0.0518),
+    ('t', 0.0741),
+    ('u', 0.0226),
+    ('v', 0.008),
+    ('w', 0.0193),
+    ('x', 0.0012),
+    ('y', 0.0162),
+    ('z', 0.0006),
+    (' ', 0.1818),
+];
 static NORMAL_WEIGHT: f64 = 400.0;
 static BOLD_WEIGHT: f64 = 700.0;
 
@@ -89,7 +118,6 @@ async fn get_font_adjustment(
         &main_descriptor.path,
     ))?;
 
-    let az_avg_width = calc_average_width(&mut font);
     let units_per_em = font
         .head_table()?
         .context(format!(
@@ -98,11 +126,10 @@ async fn get_font_adjustment(
         ))?
         .units_per_em as f64;
 
-    let fallback_avg_width = fallback_font.az_avg_width / fallback_font.units_per_em as f64;
-    // TODO: Use xWidthAvg like next/google.
-    //       JS implementation: https://github.com/seek-oss/capsize/blob/42d6dc39d58247bc6b9e013a4b1c4463bf287dca/packages/unpack/src/index.ts#L7-L83
-    let size_adjust = match az_avg_width {
-        Some(az_avg_width) => az_avg_width as f64 / units_per_em / fallback_avg_width,
+    // Same as next/font/google, which uses the `xWidthAvg` metric from capsize.
+    let fallback_avg_width = fallback_font.x_width_avg / fallback_font.units_per_em as f64;
+    let size_adjust = match calc_x_width_avg(&mut font).or_else(|| x_avg_char_width(&font)) {
+        Some(x_width_avg) => x_width_avg / units_per_em / fallback_avg_width,
         None => 1.0,
     };
 
@@ -114,27 +141,34 @@ async fn get_font_adjustment(
     })
 }
 
-fn calc_average_width(font: &mut Font<DynamicFontTableProvider>) -> Option<f32> {
-    let has_all_glyphs = AVG_CHARACTERS.chars().all(|c| {
-        font.lookup_glyph_index(c, allsorts::font::MatchingPresentation::NotRequired, None)
-            .0
-            > 0
-    });
-    if !has_all_glyphs {
-        return None;
+/// Computes the character frequency weighted average width of the font, like
+/// capsize's `xWidthAvg`. Returns `None` if the font is missing any of the
+/// weighted glyphs, e.g. for CJK or icon fonts.
+fn calc_x_width_avg(font: &mut Font<DynamicFontTableProvider>) -> Option<f64> {
+    let mut x_width_avg = 0.0;
+    for (c, weighting) in CHARACTER_WEIGHTINGS {
+        let (glyph_index, _) =
+            font.lookup_glyph_index(c, allsorts::font::MatchingPresentation::NotRequired, None);
+        if glyph_index == 0 {
+            return None;
+        }
+        x_width_avg += font.horizontal_advance(glyph_index)? as f64 * weighting;
     }
 
-    Some(
-        font.map_glyphs(
-            AVG_CHARACTERS,
-            allsorts::tag::LATN,
-            allsorts::font::MatchingPresentation::NotRequired,
-        )
-        .iter()
-        .map(|g| font.horizontal_advance(g.glyph_index).unwrap())
-        .sum::<u16>() as f32
-            / AVG_CHARACTERS.len() as f32,
-    )
+    Some(x_width_avg.round())
+}
+
+/// Reads the average character width from the font's OS/2 table, which is
+/// used when the font doesn't contain all the glyphs `xWidthAvg` is based on.
+fn x_avg_char_width(font: &Font<DynamicFontTableProvider>) -> Option<f64> {
+    let os2 = font
+        .font_table_provider
+        .table_data(allsorts::tag::OS_2)
+        .ok()??;
+    // `xAvgCharWidth` directly follows the table version in all OS/2 versions.
+    let x_avg_char_width = i16::from_be_bytes([*os2.get(2)?, *os2.get(3)?]);
+
+    (x_avg_char_width > 0).then_some(x_avg_char_width as f64)
 }
 
 /// From https://github.com/vercel/next.js/blob/dbdf47cf617b8d7213ffe1ff28318ea8eb88c623/packages/font/src/local/pick-font-file-for-fallback-generation.ts#L59
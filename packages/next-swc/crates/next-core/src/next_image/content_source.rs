@@ -1,33 +1,69 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use anyhow::{bail, Result};
+use mime::Mime;
 use turbo_tasks::{Value, Vc};
-use turbo_tasks_fs::FileSystem;
+use turbo_tasks_fs::{FileContent, FileSystem};
 use turbopack_binding::turbopack::{
     core::{
         asset::AssetContent, ident::AssetIdent, introspect::Introspectable,
         server_fs::ServerFileSystem, version::VersionedContent,
     },
     dev_server::source::{
+        headers::HeaderValue,
         query::QueryValue,
         route_tree::{RouteTree, RouteType},
         wrapping_source::{ContentSourceProcessor, WrappedGetContentSourceContent},
         ContentSource, ContentSourceContent, ContentSourceData, ContentSourceDataFilter,
-        ContentSourceDataVary, GetContentSourceContent, ProxyResult, RewriteBuilder,
+        ContentSourceDataVary, GetContentSourceContent, HeaderList, ProxyResult, RewriteBuilder,
     },
     image::process::optimize,
 };
+use url::Url;
+
+use super::{
+    format::{encode_image, is_svg, negotiate_format},
+    remote::{is_remote_image_allowed, FetchedImage, HttpImageFetcher, ImageFetcher},
+};
+use crate::next_config::{ImageConfig, ImageFormat};
 
 /// Serves, resizes, optimizes, and re-encodes images to be used with
 /// next/image.
 #[turbo_tasks::value(shared)]
 pub struct NextImageContentSource {
     asset_source: Vc<Box<dyn ContentSource>>,
+    image_config: Vc<ImageConfig>,
+    fetcher: Vc<Box<dyn ImageFetcher>>,
 }
 
 #[turbo_tasks::value_impl]
 impl NextImageContentSource {
     #[turbo_tasks::function]
-    pub fn new(asset_source: Vc<Box<dyn ContentSource>>) -> Vc<NextImageContentSource> {
-        NextImageContentSource { asset_source }.cell()
+    pub fn new(
+        asset_source: Vc<Box<dyn ContentSource>>,
+        image_config: Vc<ImageConfig>,
+    ) -> Vc<NextImageContentSource> {
+        NextImageContentSource {
+            asset_source,
+            image_config,
+            fetcher: Vc::upcast(HttpImageFetcher::new()),
+        }
+        .cell()
+    }
+
+    /// Replaces the fetcher used to download remote images.
+    #[turbo_tasks::function]
+    pub async fn with_fetcher(
+        self: Vc<Self>,
+        fetcher: Vc<Box<dyn ImageFetcher>>,
+    ) -> Result<Vc<NextImageContentSource>> {
+        let this = self.await?;
+        Ok(NextImageContentSource {
+            asset_source: this.asset_source,
+            image_config: this.image_config,
+            fetcher,
+        }
+        .cell())
     }
 }
 
@@ -47,6 +83,11 @@ impl GetContentSourceContent for NextImageContentSource {
             query: Some(ContentSourceDataFilter::Subset(
                 ["url".to_string(), "w".to_string(), "q".to_string()].into(),
             )),
+            headers: Some(ContentSourceDataFilter::Subset(
+                ["accept".to_string()].into(),
+            )),
+            // Remote images have to be refetched once they expire.
+            cache_buster: true,
             ..Default::default()
         }
         .cell()
@@ -89,7 +130,16 @@ impl GetContentSourceContent for NextImageContentSource {
             _ => bail!("missing w query argument"),
         };
 
-        // TODO: re-encode into next-gen formats.
+        let accept = match data
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get("accept"))
+        {
+            Some(HeaderValue::SingleString(accept)) => Some(accept.as_str()),
+            _ => None,
+        };
+        let image_config = this.image_config.await?;
+        let format = negotiate_format(accept, &image_config.formats);
 
         if let Some(path) = url.strip_prefix('/') {
             let sources = this.asset_source.get_routes().get(path.to_string()).await?;
@@ -98,7 +148,13 @@ impl GetContentSourceContent for NextImageContentSource {
                 .map(|s| {
                     Vc::upcast(WrappedGetContentSourceContent::new(
                         *s,
-                        Vc::upcast(NextImageContentSourceProcessor::new(path.to_string(), w, q)),
+                        Vc::upcast(NextImageContentSourceProcessor::new(
+                            path.to_string(),
+                            w,
+                            q,
+                            format,
+                            this.image_config,
+                        )),
                     ))
                 })
                 .collect();
@@ -108,16 +164,39 @@ impl GetContentSourceContent for NextImageContentSource {
             );
         }
 
-        // TODO: This should be downloaded by the server, and resized, etc.
-        Ok(ContentSourceContent::HttpProxy(
-            ProxyResult {
-                status: 302,
-                headers: vec![("Location".to_string(), url.clone())],
-                body: "".into(),
+        let remote_url = match Url::parse(url) {
+            Ok(remote_url) if matches!(remote_url.scheme(), "http" | "https") => remote_url,
+            _ => return Ok(error_response(400, "\"url\" parameter is invalid")),
+        };
+        if !is_remote_image_allowed(&remote_url, &image_config) {
+            return Ok(error_response(400, "\"url\" parameter is not allowed"));
+        }
+
+        let max_age = image_config.minimum_cache_ttl.max(1);
+        let revalidate = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / max_age as u64;
+        let file_content = match *this.fetcher.fetch(url.clone(), revalidate).await? {
+            FetchedImage::Content(file_content) => file_content,
+            FetchedImage::Error { status } => {
+                return Ok(error_response(
+                    status,
+                    "\"url\" parameter is valid but upstream response is invalid",
+                ));
             }
-            .cell(),
+        };
+        optimized_image_response(
+            this.image_config,
+            remote_url.path().trim_start_matches('/').to_string(),
+            file_content,
+            w,
+            q,
+            format,
+            200,
+            vec![(
+                "Cache-Control".to_string(),
+                format!("public, max-age={max_age}, must-revalidate"),
+            )],
         )
-        .cell())
+        .await
     }
 }
 
@@ -130,7 +209,10 @@ impl Introspectable for NextImageContentSource {
 
     #[turbo_tasks::function]
     fn details(&self) -> Vc<String> {
-        Vc::cell("supports dynamic serving of any statically imported image".to_string())
+        Vc::cell(
+            "supports dynamic serving of any statically imported or allowed remote image"
+                .to_string(),
+        )
     }
 }
 
@@ -139,16 +221,26 @@ struct NextImageContentSourceProcessor {
     path: String,
     width: u32,
     quality: u8,
+    format: Option<ImageFormat>,
+    image_config: Vc<ImageConfig>,
 }
 
 #[turbo_tasks::value_impl]
 impl NextImageContentSourceProcessor {
     #[turbo_tasks::function]
-    pub fn new(path: String, width: u32, quality: u8) -> Vc<NextImageContentSourceProcessor> {
+    pub fn new(
+        path: String,
+        width: u32,
+        quality: u8,
+        format: Option<ImageFormat>,
+        image_config: Vc<ImageConfig>,
+    ) -> Vc<NextImageContentSourceProcessor> {
         NextImageContentSourceProcessor {
             path,
             width,
             quality,
+            format,
+            image_config,
         }
         .cell()
     }
@@ -166,15 +258,276 @@ impl ContentSourceProcessor for NextImageContentSourceProcessor {
         let AssetContent::File(file_content) = *asset_content else {
             return Ok(content);
         };
-        let optimized_file_content = optimize(
-            AssetIdent::from_path(ServerFileSystem::new().root().join(self.path.clone())),
+        optimized_image_response(
+            self.image_config,
+            self.path.clone(),
             file_content,
             self.width,
-            u32::MAX,
             self.quality,
-        );
-        Ok(ContentSourceContent::static_content(
-            AssetContent::File(optimized_file_content).into(),
-        ))
+            self.format,
+            static_content.status_code,
+            vec![],
+        )
+        .await
+    }
+}
+
+/// Resizes the image at `path` to `width`, re-encodes it into `format` and
+/// attaches the headers production sends along with optimized images.
+async fn optimized_image_response(
+    image_config: Vc<ImageConfig>,
+    path: String,
+    file_content: Vc<FileContent>,
+    width: u32,
+    quality: u8,
+    format: Option<ImageFormat>,
+    status_code: u16,
+    mut headers: Vec<(String, String)>,
+) -> Result<Vc<ContentSourceContent>> {
+    let image_config = image_config.await?;
+    if !image_config.dangerously_allow_svg && is_svg(&path, file_content).await? {
+        return Ok(error_response(
+            400,
+            "\"url\" parameter is valid but image type is not allowed",
+        ));
+    }
+
+    let optimized_file_content = optimize(
+        AssetIdent::from_path(ServerFileSystem::new().root().join(path.clone())),
+        file_content,
+        width,
+        u32::MAX,
+        quality,
+    );
+    let optimized_file_content = match format {
+        Some(format) => encode_image(optimized_file_content, format, quality).await?,
+        None => optimized_file_content,
+    };
+
+    // The response depends on the negotiated format, so caches must key it by
+    // the `Accept` header.
+    headers.push(("Vary".to_string(), "Accept".to_string()));
+    let content_type = match &*optimized_file_content.await? {
+        FileContent::Content(file) => file.content_type().cloned(),
+        FileContent::NotFound => None,
+    };
+    headers.push((
+        "Content-Disposition".to_string(),
+        format!(
+            "inline; filename=\"{}\"",
+            file_name(&path, content_type.as_ref())
+        ),
+    ));
+    if !image_config.content_security_policy.is_empty() {
+        headers.push((
+            "Content-Security-Policy".to_string(),
+            image_config.content_security_policy.clone(),
+        ));
+    }
+
+    Ok(ContentSourceContent::static_with_headers(
+        AssetContent::File(optimized_file_content).into(),
+        status_code,
+        HeaderList::new(headers),
+    ))
+}
+
+/// The file name an optimized image is served as: the name of the original
+/// image with the extension of the format it was encoded into.
+fn file_name(path: &str, content_type: Option<&Mime>) -> String {
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let (stem, extension) = name.rsplit_once('.').unwrap_or((name, ""));
+    // Keep the original extension when it matches the content type, as there
+    // are several for e.g. JPEGs.
+    let extension = match content_type.and_then(mime_guess::get_mime_extensions) {
+        Some(extensions) if !extensions.contains(&extension) => {
+            extensions.first().copied().unwrap_or(extension)
+        }
+        _ => extension,
+    };
+    if extension.is_empty() {
+        stem.to_string()
+    } else {
+        format!("{stem}.{extension}")
+    }
+}
+
+fn error_response(status: u16, message: &'static str) -> Vc<ContentSourceContent> {
+    ContentSourceContent::HttpProxy(
+        ProxyResult {
+            status,
+            headers: vec![],
+            body: message.into(),
+        }
+        .cell(),
+    )
+    .cell()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use anyhow::{bail, Result};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use turbo_tasks::{TurboTasks, Value, Vc};
+    use turbo_tasks_fs::{File, FileContent};
+    use turbopack_binding::{
+        turbo::tasks_memory::MemoryBackend,
+        turbopack::{
+            core::{asset::AssetContent, version::VersionedContent},
+            dev_server::source::{
+                query::Query, ContentSourceContent, ContentSourceData, GetContentSourceContent,
+                NoContentSource,
+            },
+        },
+    };
+
+    use super::NextImageContentSource;
+    use crate::{
+        next_config::ImageConfig,
+        next_image::remote::{FetchedImage, ImageFetcher},
+    };
+
+    /// Answers every request like an upstream which doesn't have the image.
+    #[turbo_tasks::value]
+    struct NotFoundFetcher;
+
+    #[turbo_tasks::value_impl]
+    impl ImageFetcher for NotFoundFetcher {
+        #[turbo_tasks::function]
+        fn fetch(&self, _url: String, _revalidate: u64) -> Vc<FetchedImage> {
+            FetchedImage::Error { status: 404 }.cell()
+        }
+    }
+
+    /// A red 128x64 PNG.
+    const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAIAAAABACAIAAABdtOgoAAAAcklEQVR42u3RMQ0AAAjAMJTMvxRkIYOnyRSss6XHxgIAAAQAgAAAEAAAAgBAAAAIAAABACAAAAQAgAAAEAAAAgBAAAAIAAABACAAAAQAgAAAEAAAAgBAAAAIAAABACAAAAQAgAAAEAAAAgBAAAAIAADVAUvPgeGdgWQ0AAAAAElFTkSuQmCC";
+
+    /// Serves [PNG] when asked for the `revalidate` bucket the test expects,
+    /// and fails otherwise.
+    #[turbo_tasks::value]
+    struct PngFetcher {
+        revalidate: u64,
+    }
+
+    #[turbo_tasks::value_impl]
+    impl ImageFetcher for PngFetcher {
+        #[turbo_tasks::function]
+        fn fetch(&self, _url: String, revalidate: u64) -> Result<Vc<FetchedImage>> {
+            // The bucket may have rolled over since the test computed it.
+            if revalidate != self.revalidate && revalidate != self.revalidate + 1 {
+                return Ok(FetchedImage::Error { status: 500 }.cell());
+            }
+            let file = File::from(&*STANDARD.decode(PNG)?).with_content_type(mime::IMAGE_PNG);
+            Ok(FetchedImage::Content(FileContent::Content(file).cell()).cell())
+        }
+    }
+
+    /// The status of the error response for the image at `url`.
+    async fn error_status(source: Vc<NextImageContentSource>, url: &str) -> Result<u16> {
+        let query = serde_json::from_value::<Query>(serde_json::json!({ "url": url, "w": "64" }))?;
+        let content = source
+            .get(
+                String::new(),
+                Value::new(ContentSourceData {
+                    query: Some(query),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        let ContentSourceContent::HttpProxy(proxy) = &*content else {
+            bail!("expected an error response for {url}");
+        };
+        Ok(proxy.await?.status)
+    }
+
+    #[tokio::test]
+    async fn rejects_disallowed_and_failed_remote_images() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        tt.run_once(async move {
+            let image_config = ImageConfig {
+                domains: vec!["example.com".to_string()],
+                ..Default::default()
+            };
+            let source = NextImageContentSource::new(
+                Vc::upcast(NoContentSource::new()),
+                image_config.cell(),
+            )
+            .with_fetcher(Vc::upcast(NotFoundFetcher.cell()));
+
+            // Not in the allow-list, so the fetcher is never asked.
+            assert_eq!(error_status(source, "https://evil.com/a.png").await?, 400);
+            assert_eq!(error_status(source, "ftp://example.com/a.png").await?, 400);
+            // Allowed, but the upstream doesn't have it.
+            assert_eq!(
+                error_status(source, "https://example.com/a.png").await?,
+                404
+            );
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn optimizes_remote_images() {
+        crate::register();
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        tt.run_once(async move {
+            let minimum_cache_ttl = 3600;
+            let revalidate =
+                SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / minimum_cache_ttl as u64;
+            let image_config = ImageConfig {
+                domains: vec!["example.com".to_string()],
+                minimum_cache_ttl,
+                ..Default::default()
+            };
+            let source = NextImageContentSource::new(
+                Vc::upcast(NoContentSource::new()),
+                image_config.cell(),
+            )
+            .with_fetcher(Vc::upcast(PngFetcher { revalidate }.cell()));
+
+            let query = serde_json::from_value::<Query>(serde_json::json!({
+                "url": "https://example.com/images/red.png",
+                "w": "64",
+            }))?;
+            let content = source
+                .get(
+                    String::new(),
+                    Value::new(ContentSourceData {
+                        query: Some(query),
+                        ..Default::default()
+                    }),
+                )
+                .await?;
+            let ContentSourceContent::Static(content) = &*content else {
+                bail!("expected the optimized image");
+            };
+            let content = content.await?;
+            assert_eq!(content.status_code, 200);
+            assert!(content.headers.await?.contains(&(
+                "Cache-Control".to_string(),
+                "public, max-age=3600, must-revalidate".to_string()
+            )));
+
+            let AssetContent::File(file_content) = &*content.content.content().await? else {
+                bail!("expected a file");
+            };
+            let FileContent::Content(file) = &*file_content.await? else {
+                bail!("expected the image to exist");
+            };
+            assert_eq!(file.content_type(), Some(&mime::IMAGE_PNG));
+            let bytes = file.content().to_bytes()?;
+            assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+            // The width and height in the IHDR chunk.
+            assert_eq!(bytes[16..20], 64u32.to_be_bytes());
+            assert_eq!(bytes[20..24], 32u32.to_be_bytes());
+            Ok(())
+        })
+        .await
+        .unwrap();
     }
 }
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileContent;
+
+use crate::next_config::ImageFormat;
+
+impl ImageFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Webp => "image/webp",
+            ImageFormat::Avif => "image/avif",
+        }
+    }
+
+    /// Whether this binary was compiled with an encoder for the format.
+    fn is_supported(&self) -> bool {
+        match self {
+            ImageFormat::Webp => cfg!(feature = "image-webp"),
+            ImageFormat::Avif => cfg!(feature = "image-avif"),
+        }
+    }
+}
+
+/// Picks the format an image should be re-encoded into, based on the
+/// request's `Accept` header and the formats enabled in `images.formats`.
+///
+/// Like Next.js, the format with the highest quality value in the `Accept`
+/// header wins, and ties are broken by the order of `images.formats`. Only
+/// formats that are explicitly listed in the header are considered, wildcards
+/// are not.
+pub(crate) fn negotiate_format(
+    accept: Option<&str>,
+    formats: &[ImageFormat],
+) -> Option<ImageFormat> {
+    let accept = accept?;
+    let mut best: Option<(ImageFormat, f32)> = None;
+    for format in formats.iter().filter(|format| format.is_supported()) {
+        let Some(q) = accept_quality(accept, format.mime_type()) else {
+            continue;
+        };
+        if q > 0.0 && best.map_or(true, |(_, best_q)| q > best_q) {
+            best = Some((*format, q));
+        }
+    }
+    best.map(|(format, _)| format)
+}
+
+/// Returns the quality value the `Accept` header assigns to `mime_type`, if
+/// it lists it at all.
+fn accept_quality(accept: &str, mime_type: &str) -> Option<f32> {
+    accept.split(',').find_map(|range| {
+        let mut params = range.split(';').map(str::trim);
+        if !params.next()?.eq_ignore_ascii_case(mime_type) {
+            return None;
+        }
+        params
+            .find_map(|param| param.strip_prefix("q="))
+            .map_or(Some(1.0), |q| q.parse::<f32>().ok())
+    })
+}
+
+/// Whether the image at `path` is an SVG, either by its content type, its
+/// extension or its content.
+pub(crate) async fn is_svg(path: &str, content: Vc<FileContent>) -> Result<bool> {
+    if path.ends_with(".svg") {
+        return Ok(true);
+    }
+    let content = content.await?;
+    let FileContent::Content(file) = &*content else {
+        return Ok(false);
+    };
+    if file
+        .content_type()
+        .map_or(false, |mime| mime.subtype() == mime::SVG)
+    {
+        return Ok(true);
+    }
+    let bytes = file.content().to_bytes()?;
+    let start = &bytes[bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(bytes.len())..];
+    Ok(start.starts_with(b"<svg") || start.starts_with(b"<?xml"))
+}
+
+/// Re-encodes already optimized image content into `format`.
+///
+/// SVGs and GIFs are served as is, and so is content that can't be decoded.
+#[cfg(any(feature = "image-webp", feature = "image-avif"))]
+pub(crate) async fn encode_image(
+    content: Vc<FileContent>,
+    format: ImageFormat,
+    quality: u8,
+) -> Result<Vc<FileContent>> {
+    use anyhow::Context;
+    use mime::Mime;
+    use turbo_tasks_fs::File;
+
+    let file_content = content.await?;
+    let FileContent::Content(file) = &*file_content else {
+        return Ok(content);
+    };
+    if file.content_type().map_or(false, |mime| {
+        mime.subtype() == mime::SVG || mime.subtype() == mime::GIF
+    }) {
+        return Ok(content);
+    }
+    let bytes = file.content().to_bytes()?;
+    let Ok(image) = image::load_from_memory(&bytes) else {
+        return Ok(content);
+    };
+    let encoded = match format {
+        #[cfg(feature = "image-webp")]
+        ImageFormat::Webp => {
+            // `webp`'s `image` integration is disabled, as `image` depends on `webp`
+            // itself.
+            let image = image.to_rgba8();
+            webp::Encoder::from_rgba(image.as_raw(), image.width(), image.height())
+                .encode(quality as f32)
+                .to_vec()
+        }
+        #[cfg(feature = "image-avif")]
+        ImageFormat::Avif => {
+            use image::{codecs::avif::AvifEncoder, ColorType, ImageEncoder};
+
+            let image = image.to_rgba8();
+            let mut buf = Vec::new();
+            AvifEncoder::new_with_speed_quality(&mut buf, 6, quality)
+                .write_image(
+                    image.as_raw(),
+                    image.width(),
+                    image.height(),
+                    ColorType::Rgba8,
+                )
+                .context("unable to encode image as avif")?;
+            buf
+        }
+        #[allow(unreachable_patterns)]
+        _ => return Ok(content),
+    };
+    let mime_type: Mime = format
+        .mime_type()
+        .parse()
+        .context("image format has an invalid mime type")?;
+    Ok(FileContent::Content(File::from(encoded.as_slice()).with_content_type(mime_type)).cell())
+}
+
+/// Without any encoders compiled in, no format is ever negotiated.
+#[cfg(not(any(feature = "image-webp", feature = "image-avif")))]
+pub(crate) async fn encode_image(
+    content: Vc<FileContent>,
+    _format: ImageFormat,
+    _quality: u8,
+) -> Result<Vc<FileContent>> {
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::accept_quality;
+
+    #[test]
+    fn reads_accept_quality() {
+        let accept = "image/avif;q=0.8, image/webp, image/*;q=0.5";
+        assert_eq!(accept_quality(accept, "image/avif"), Some(0.8));
+        assert_eq!(accept_quality(accept, "image/webp"), Some(1.0));
+        assert_eq!(accept_quality(accept, "image/png"), None);
+        assert_eq!(accept_quality("image/webp;q=0", "image/webp"), Some(0.0));
+    }
+}
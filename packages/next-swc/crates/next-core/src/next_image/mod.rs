@@ -1,5 +1,7 @@
 pub(crate) mod content_source;
+pub(crate) mod format;
 pub(crate) mod module;
+pub(crate) mod remote;
 pub(crate) mod source_asset;
 
 pub use module::StructuredImageModuleType;
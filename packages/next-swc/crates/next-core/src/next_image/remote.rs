@@ -0,0 +1,171 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileContent};
+use url::Url;
+
+use crate::next_config::{ImageConfig, RemotePatternProtocal};
+
+/// A remote image fetched by an [ImageFetcher].
+#[turbo_tasks::value(shared)]
+pub enum FetchedImage {
+    Content(Vc<FileContent>),
+    /// The image couldn't be fetched. `status` is the upstream status code, or
+    /// 500 when the upstream couldn't be reached at all.
+    Error {
+        status: u16,
+    },
+}
+
+/// Fetches remote images for the image endpoint.
+///
+/// Responses are cached per `(url, revalidate)`. The image endpoint bumps
+/// `revalidate` once `images.minimumCacheTTL` has passed, so implementations
+/// should always hit the network.
+#[turbo_tasks::value_trait]
+pub trait ImageFetcher {
+    fn fetch(self: Vc<Self>, url: String, revalidate: u64) -> Vc<FetchedImage>;
+}
+
+/// Fetches remote images over HTTP(S).
+#[turbo_tasks::value(shared)]
+pub struct HttpImageFetcher;
+
+#[turbo_tasks::value_impl]
+impl HttpImageFetcher {
+    #[turbo_tasks::function]
+    pub fn new() -> Vc<Self> {
+        HttpImageFetcher.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImageFetcher for HttpImageFetcher {
+    #[turbo_tasks::function]
+    async fn fetch(&self, url: String, _revalidate: u64) -> Result<Vc<FetchedImage>> {
+        let response = match reqwest::get(&url).await.and_then(|r| r.error_for_status()) {
+            Ok(response) => response,
+            Err(err) => {
+                return Ok(FetchedImage::Error {
+                    status: err.status().map_or(500, |status| status.as_u16()),
+                }
+                .cell())
+            }
+        };
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<mime::Mime>().ok());
+        let Ok(bytes) = response.bytes().await else {
+            return Ok(FetchedImage::Error { status: 500 }.cell());
+        };
+        let file = File::from(&*bytes);
+        let file = match content_type {
+            Some(content_type) => file.with_content_type(content_type),
+            None => file,
+        };
+        Ok(FetchedImage::Content(FileContent::Content(file).cell()).cell())
+    }
+}
+
+/// Whether `url` may be optimized according to `images.domains` and
+/// `images.remotePatterns`.
+pub(crate) fn is_remote_image_allowed(url: &Url, config: &ImageConfig) -> bool {
+    let Some(hostname) = url.host_str() else {
+        return false;
+    };
+    if config.domains.iter().any(|domain| domain == hostname) {
+        return true;
+    }
+    config.remote_patterns.iter().any(|pattern| {
+        if let Some(protocol) = &pattern.protocol {
+            let scheme = match protocol {
+                RemotePatternProtocal::Http => "http",
+                RemotePatternProtocal::Https => "https",
+            };
+            if url.scheme() != scheme {
+                return false;
+            }
+        }
+        if let Some(port) = &pattern.port {
+            let actual_port = url.port().map(|port| port.to_string()).unwrap_or_default();
+            if *port != actual_port {
+                return false;
+            }
+        }
+        glob_match(&pattern.hostname, hostname, '.')
+            && glob_match(pattern.pathname.as_deref().unwrap_or("**"), url.path(), '/')
+    })
+}
+
+/// Matches `input` against a glob where `*` matches within a single segment
+/// and `**` matches any number of segments, as `remotePatterns` documents.
+fn glob_match(pattern: &str, input: &str, separator: char) -> bool {
+    let pattern = pattern.split(separator).collect::<Vec<_>>();
+    let input = input.split(separator).collect::<Vec<_>>();
+    match_segments(&pattern, &input)
+}
+
+fn match_segments(pattern: &[&str], input: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => input.is_empty(),
+        Some((&"**", rest)) => (0..=input.len()).any(|skip| match_segments(rest, &input[skip..])),
+        Some((segment, rest)) => match input.split_first() {
+            Some((actual, input)) => match_segment(segment, actual) && match_segments(rest, input),
+            None => false,
+        },
+    }
+}
+
+fn match_segment(pattern: &str, input: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == input,
+        Some((prefix, rest)) => {
+            let Some(input) = input.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=input.len())
+                .filter(|&i| input.is_char_boundary(i))
+                .any(|i| match_segment(rest, &input[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{glob_match, is_remote_image_allowed};
+    use crate::next_config::{ImageConfig, RemotePattern, RemotePatternProtocal};
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_match("*.example.com", "img.example.com", '.'));
+        assert!(!glob_match("*.example.com", "a.img.example.com", '.'));
+        assert!(glob_match("**.example.com", "a.img.example.com", '.'));
+        assert!(glob_match("/images/**", "/images/a/b.png", '/'));
+        assert!(glob_match("/images/*.png", "/images/b.png", '/'));
+        assert!(!glob_match("/images/*.png", "/images/a/b.png", '/'));
+    }
+
+    #[test]
+    fn allows_configured_remote_images() {
+        let config = ImageConfig {
+            domains: vec!["example.com".to_string()],
+            remote_patterns: vec![RemotePattern {
+                hostname: "**.cdn.dev".to_string(),
+                protocol: Some(RemotePatternProtocal::Https),
+                port: None,
+                pathname: Some("/assets/**".to_string()),
+            }],
+            ..Default::default()
+        };
+        let allowed = |url: &str| is_remote_image_allowed(&Url::parse(url).unwrap(), &config);
+
+        assert!(allowed("http://example.com/a.png"));
+        assert!(allowed("https://img.cdn.dev/assets/a.png"));
+        assert!(!allowed("http://img.cdn.dev/assets/a.png"));
+        assert!(!allowed("https://img.cdn.dev/other/a.png"));
+        assert!(!allowed("https://evil.com/a.png"));
+    }
+}
@@ -24,6 +24,7 @@ use turbopack_binding::{
 use crate::{
     embed_js::{next_js_fs, VIRTUAL_PACKAGE_NAME},
     mode::NextMode,
+    next_barrel::NextBarrelOptimizeReplacer,
     next_client::context::ClientContextType,
     next_config::NextConfig,
     next_font::{
@@ -44,6 +45,7 @@ use crate::{
 pub async fn get_next_client_import_map(
     project_path: Vc<FileSystemPath>,
     ty: Value<ClientContextType>,
+    mode: NextMode,
     next_config: Vc<NextConfig>,
     execution_context: Vc<ExecutionContext>,
 ) -> Result<Vc<ImportMap>> {
@@ -52,6 +54,7 @@ pub async fn get_next_client_import_map(
     insert_next_shared_aliases(
         &mut import_map,
         project_path,
+        mode,
         execution_context,
         next_config,
     )
@@ -238,6 +241,7 @@ pub fn get_next_client_fallback_import_map(ty: Value<ClientContextType>) -> Vc<I
 pub async fn get_next_server_import_map(
     project_path: Vc<FileSystemPath>,
     ty: Value<ServerContextType>,
+    mode: NextMode,
     next_config: Vc<NextConfig>,
     execution_context: Vc<ExecutionContext>,
 ) -> Result<Vc<ImportMap>> {
@@ -246,6 +250,7 @@ pub async fn get_next_server_import_map(
     insert_next_shared_aliases(
         &mut import_map,
         project_path,
+        mode,
         execution_context,
         next_config,
     )
@@ -313,6 +318,7 @@ pub async fn get_next_server_import_map(
 pub async fn get_next_edge_import_map(
     project_path: Vc<FileSystemPath>,
     ty: Value<ServerContextType>,
+    mode: NextMode,
     next_config: Vc<NextConfig>,
     execution_context: Vc<ExecutionContext>,
 ) -> Result<Vc<ImportMap>> {
@@ -371,6 +377,7 @@ pub async fn get_next_edge_import_map(
     insert_next_shared_aliases(
         &mut import_map,
         project_path,
+        mode,
         execution_context,
         next_config,
     )
@@ -709,6 +716,7 @@ async fn insert_optimized_module_aliases(
 async fn insert_next_shared_aliases(
     import_map: &mut ImportMap,
     project_path: Vc<FileSystemPath>,
+    mode: NextMode,
     execution_context: Vc<ExecutionContext>,
     next_config: Vc<NextConfig>,
 ) -> Result<()> {
@@ -732,6 +740,13 @@ async fn insert_next_shared_aliases(
         package_root,
     );
 
+    // Responses from Google Fonts are persisted here, so builds can reuse them
+    // without network access.
+    let font_cache_dir = project_path.join(format!(
+        "{}/cache/fonts",
+        next_config.await?.dist_dir.as_deref().unwrap_or(".next")
+    ));
+
     import_map.insert_alias(
         // Request path from js via next-font swc transform
         AliasPattern::exact("next/font/google/target.css"),
@@ -748,7 +763,9 @@ async fn insert_next_shared_aliases(
         AliasPattern::exact("@vercel/turbopack-next/internal/font/google/cssmodule.module.css"),
         ImportMapping::Dynamic(Vc::upcast(NextFontGoogleCssModuleReplacer::new(
             project_path,
+            mode,
             execution_context,
+            font_cache_dir,
         )))
         .into(),
     );
@@ -757,6 +774,8 @@ async fn insert_next_shared_aliases(
         AliasPattern::exact("@vercel/turbopack-next/internal/font/google/font"),
         ImportMapping::Dynamic(Vc::upcast(NextFontGoogleFontFileReplacer::new(
             project_path,
+            mode,
+            font_cache_dir,
         )))
         .into(),
     );
@@ -786,6 +805,12 @@ async fn insert_next_shared_aliases(
         ImportMapping::Dynamic(Vc::upcast(NextFontLocalFontFileReplacer::new(project_path))).into(),
     );
 
+    import_map.insert_alias(
+        // Request path from js via the named import transform of optimizePackageImports
+        AliasPattern::exact("__barrel_optimize__"),
+        ImportMapping::Dynamic(Vc::upcast(NextBarrelOptimizeReplacer::new(project_path))).into(),
+    );
+
     import_map.insert_singleton_alias("@swc/helpers", get_next_package(project_path));
     import_map.insert_singleton_alias("styled-jsx", get_next_package(project_path));
     import_map.insert_singleton_alias("next", project_path);
@@ -838,7 +863,7 @@ async fn insert_next_shared_aliases(
 }
 
 #[turbo_tasks::function]
-async fn package_lookup_resolve_options(
+pub(crate) async fn package_lookup_resolve_options(
     project_path: Vc<FileSystemPath>,
 ) -> Result<Vc<ResolveOptions>> {
     Ok(resolve_options(
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_binding::turbopack::core::{
+    asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+};
+
+use super::{ImagesManifest, ImagesManifestConfig};
+use crate::next_config::NextConfig;
+
+/// Builds the `images-manifest.json` `next start` uses to serve
+/// `/_next/image`.
+#[turbo_tasks::function]
+pub async fn build_images_manifest(
+    node_root: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<Vc<Box<dyn OutputAsset>>> {
+    let mut config = next_config.image_config().await?.clone_value();
+    // Consumers of the manifest match against regular expressions, not globs.
+    for pattern in config.remote_patterns.iter_mut() {
+        pattern.hostname = glob_to_regex(&pattern.hostname, false);
+        pattern.pathname = Some(glob_to_regex(
+            pattern.pathname.as_deref().unwrap_or("**"),
+            true,
+        ));
+    }
+    let sizes = config
+        .device_sizes
+        .iter()
+        .chain(config.image_sizes.iter())
+        .copied()
+        .collect();
+    let images_manifest = ImagesManifest {
+        version: 1,
+        images: ImagesManifestConfig { config, sizes },
+    };
+
+    Ok(Vc::upcast(VirtualOutputAsset::new(
+        node_root.join("images-manifest.json".to_string()),
+        AssetContent::file(File::from(serde_json::to_string_pretty(&images_manifest)?).into()),
+    )))
+}
+
+/// Converts a `remotePatterns` glob to the source of a regular expression which
+/// matches like picomatch's `makeRe(glob, { dot }).source` does: `*` matches
+/// within a path segment and a `**` segment matches any number of segments.
+/// Unless `dot` is set, wildcards don't match segments starting with a dot.
+fn glob_to_regex(glob: &str, dot: bool) -> String {
+    let any_segment = if dot { "[^/]*?" } else { "(?!\\.)[^/]*?" };
+    let mut segments = glob.split('/').collect::<Vec<_>>();
+    // `**/**` matches the same paths as `**`.
+    segments.dedup_by(|segment, previous| *segment == "**" && *previous == "**");
+    let last = segments.len() - 1;
+
+    let mut source = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        if *segment == "**" {
+            if i == last && i > 0 {
+                // `a/**` matches `a` itself as well.
+                source.truncate(source.len() - "\\/".len());
+                source.push_str(&format!("(?:\\/{any_segment})*\\/?"));
+            } else if i == last {
+                source.push_str(&format!("(?:{any_segment}(?:\\/{any_segment})*)?"));
+            } else {
+                source.push_str(&format!("(?:{any_segment}\\/)*"));
+            }
+            continue;
+        }
+
+        if !dot && segment.starts_with(['*', '?']) {
+            source.push_str("(?!\\.)");
+        }
+        let mut chars = segment.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => {
+                    while chars.next_if_eq(&'*').is_some() {}
+                    source.push_str("[^/]*?");
+                }
+                '?' => source.push_str("[^/]"),
+                c => source.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        if i != last {
+            source.push_str("\\/");
+        }
+    }
+    format!("^(?:{source})$")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_to_regex;
+
+    #[test]
+    fn converts_hostnames() {
+        assert_eq!(glob_to_regex("example.com", false), r"^(?:example\.com)$");
+        assert_eq!(
+            glob_to_regex("*.example.com", false),
+            r"^(?:(?!\.)[^/]*?\.example\.com)$"
+        );
+    }
+
+    #[test]
+    fn converts_pathnames() {
+        assert_eq!(glob_to_regex("**", true), r"^(?:(?:[^/]*?(?:\/[^/]*?)*)?)$");
+        assert_eq!(
+            glob_to_regex("/images/**", true),
+            r"^(?:\/images(?:\/[^/]*?)*\/?)$"
+        );
+        assert_eq!(
+            glob_to_regex("/images/*.png", true),
+            r"^(?:\/images\/[^/]*?\.png)$"
+        );
+        assert_eq!(glob_to_regex("/a/**/b", true), r"^(?:\/a\/(?:[^/]*?\/)*b)$");
+        assert_eq!(glob_to_regex("a/**/**", true), r"^(?:a(?:\/[^/]*?)*\/?)$");
+        assert_eq!(
+            glob_to_regex("/a/**/**/b", true),
+            r"^(?:\/a\/(?:[^/]*?\/)*b)$"
+        );
+    }
+}
@@ -1,14 +1,27 @@
 //! Type definitions for the Next.js manifest formats.
 
 pub(crate) mod client_reference_manifest;
+pub(crate) mod images_manifest;
+pub(crate) mod routes_manifest;
+pub(crate) mod subresource_integrity_manifest;
 
 use std::collections::HashMap;
 
-use indexmap::IndexSet;
-use serde::{Deserialize, Serialize};
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize, Serializer};
 use turbo_tasks::{trace::TraceRawVcs, TaskInput};
 
-use crate::next_config::{CrossOriginConfig, Rewrites};
+pub use self::{
+    images_manifest::build_images_manifest,
+    routes_manifest::build_routes_manifest,
+    subresource_integrity_manifest::{
+        client_assets_integrity, subresource_integrity_manifest, AssetsIntegrity,
+    },
+};
+use crate::{
+    app_segment_config::NextRevalidate,
+    next_config::{CrossOriginConfig, Header, I18NConfig, ImageConfig, Redirect, Rewrites},
+};
 
 #[derive(Serialize, Default, Debug)]
 pub struct PagesManifest {
@@ -26,6 +39,10 @@ pub struct BuildManifest {
     pub root_main_files: Vec<String>,
     pub pages: HashMap<String, Vec<String>>,
     pub amp_first_pages: Vec<String>,
+    /// The subresource integrity of the files listed in `pages` and
+    /// `root_main_files`, when enabled.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub integrity: HashMap<String, String>,
 }
 
 #[derive(Serialize, Debug)]
@@ -67,6 +84,19 @@ pub enum RouteHas {
     },
 }
 
+impl From<crate::next_config::RouteHas> for RouteHas {
+    fn from(value: crate::next_config::RouteHas) -> Self {
+        use crate::next_config::RouteHas as ConfigRouteHas;
+
+        match value {
+            ConfigRouteHas::Header { key, value } => RouteHas::Header { key, value },
+            ConfigRouteHas::Cookie { key, value } => RouteHas::Cookie { key, value },
+            ConfigRouteHas::Query { key, value } => RouteHas::Query { key, value },
+            ConfigRouteHas::Host { value } => RouteHas::Host { value },
+        }
+    }
+}
+
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct MiddlewareMatcher {
@@ -98,6 +128,10 @@ pub struct EdgeFunctionDefinition {
     pub assets: Vec<AssetBinding>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub regions: Option<Regions>,
+    #[serde(rename = "maxDuration", skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<u32>,
+    #[serde(rename = "experimentalPPR", skip_serializing_if = "Option::is_none")]
+    pub experimental_ppr: Option<bool>,
 }
 
 #[derive(Serialize, Default, Debug)]
@@ -132,6 +166,164 @@ pub struct MiddlewaresManifestV2 {
     pub functions: HashMap<String, EdgeFunctionDefinition>,
 }
 
+/// The config of serverless functions, keyed by page. Only functions with a
+/// non-default config are listed.
+#[derive(Serialize, Debug)]
+pub struct FunctionsConfigManifest {
+    pub version: u32,
+    pub functions: HashMap<String, FunctionConfig>,
+}
+
+impl Default for FunctionsConfigManifest {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            functions: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<u32>,
+    #[serde(rename = "experimentalPPR", skip_serializing_if = "Option::is_none")]
+    pub experimental_ppr: Option<bool>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutesManifest {
+    pub version: u32,
+    pub pages404: bool,
+    pub case_sensitive: bool,
+    pub base_path: String,
+    pub redirects: Vec<Redirect>,
+    pub headers: Vec<Header>,
+    pub rewrites: Rewrites,
+    pub static_routes: Vec<RoutesManifestRoute>,
+    pub dynamic_routes: Vec<RoutesManifestRoute>,
+    pub data_routes: Vec<RoutesManifestDataRoute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub i18n: Option<I18NConfig>,
+    pub rsc: RscRoutesConfig,
+    pub skip_middleware_url_normalize: bool,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutesManifestRoute {
+    pub page: String,
+    pub regex: String,
+    pub route_keys: IndexMap<String, String>,
+    pub named_regex: String,
+}
+
+/// The route of the `/_next/data` requests for a page with `getStaticProps` or
+/// `getServerSideProps`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutesManifestDataRoute {
+    pub page: String,
+    /// Only set for dynamic pages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route_keys: Option<IndexMap<String, String>>,
+    pub data_route_regex: String,
+    /// Only set for dynamic pages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_data_route_regex: Option<String>,
+}
+
+/// The headers used by the app router to request RSC payloads.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RscRoutesConfig {
+    pub header: String,
+    pub vary_header: String,
+    pub prefetch_header: String,
+    pub content_type_header: String,
+}
+
+impl Default for RscRoutesConfig {
+    fn default() -> Self {
+        Self {
+            header: "RSC".to_string(),
+            vary_header: "RSC, Next-Router-State-Tree, Next-Router-Prefetch".to_string(),
+            prefetch_header: "Next-Router-Prefetch".to_string(),
+            content_type_header: "text/x-component".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PrerenderManifest {
+    pub version: u32,
+    pub routes: HashMap<String, SsgRoute>,
+    pub dynamic_routes: HashMap<String, DynamicSsgRoute>,
+    pub not_found_routes: Vec<String>,
+    pub preview: PreviewProps,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SsgRoute {
+    #[serde(serialize_with = "serialize_revalidate")]
+    pub initial_revalidate_seconds: NextRevalidate,
+    pub src_route: Option<String>,
+    pub data_route: Option<String>,
+    #[serde(rename = "experimentalPPR", skip_serializing_if = "Option::is_none")]
+    pub experimental_ppr: Option<bool>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicSsgRoute {
+    pub route_regex: String,
+    pub data_route: Option<String>,
+    pub data_route_regex: Option<String>,
+    /// `null` renders unknown params on demand, `false` responds with a 404.
+    pub fallback: Option<bool>,
+    #[serde(rename = "experimentalPPR", skip_serializing_if = "Option::is_none")]
+    pub experimental_ppr: Option<bool>,
+}
+
+/// Serializes a revalidate value the way Next.js expects it: the number of
+/// seconds, or `false` to never revalidate.
+fn serialize_revalidate<S: Serializer>(
+    revalidate: &NextRevalidate,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match revalidate {
+        NextRevalidate::Never | NextRevalidate::ForceCache => serializer.serialize_bool(false),
+        NextRevalidate::Frequency { seconds } => serializer.serialize_u32(*seconds),
+    }
+}
+
+/// The secrets used to sign and encrypt preview mode cookies.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, TaskInput, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewProps {
+    pub preview_mode_id: String,
+    pub preview_mode_signing_key: String,
+    pub preview_mode_encryption_key: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImagesManifest {
+    pub version: u32,
+    pub images: ImagesManifestConfig,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImagesManifestConfig {
+    #[serde(flatten)]
+    pub config: ImageConfig,
+    /// The device and image sizes combined.
+    pub sizes: Vec<u16>,
+}
+
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ReactLoadableManifest {
@@ -294,6 +486,9 @@ pub struct FontManifestEntry {
 #[serde(rename_all = "camelCase")]
 pub struct AppBuildManifest {
     pub pages: HashMap<String, Vec<String>>,
+    /// The subresource integrity of the files listed in `pages`, when enabled.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub integrity: HashMap<String, String>,
 }
 
 // TODO(alexkirsz) Unify with the one for dev.
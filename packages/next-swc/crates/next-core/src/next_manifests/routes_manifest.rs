@@ -0,0 +1,173 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_binding::turbopack::core::{
+    asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+};
+
+use super::{RoutesManifest, RoutesManifestDataRoute, RoutesManifestRoute, RscRoutesConfig};
+use crate::{
+    next_config::{Headers, NextConfig, Redirects, Rewrites},
+    next_edge::route_regex::{get_named_route_regex, is_dynamic_route},
+    url_node::get_sorted_routes,
+};
+
+/// Pages which are never matched by a route of their own.
+fn is_reserved_page(page: &str) -> bool {
+    matches!(page, "/_app" | "/_error" | "/_document")
+        || page == "/api"
+        || page.starts_with("/api/")
+}
+
+fn manifest_route(page: &str) -> RoutesManifestRoute {
+    let named_route_regex = get_named_route_regex(page);
+    RoutesManifestRoute {
+        page: page.to_string(),
+        regex: named_route_regex.regex.regex,
+        route_keys: named_route_regex.route_keys,
+        // The manifest is read by JavaScript, which doesn't support the `(?P<name>)`
+        // syntax for named groups.
+        named_regex: named_route_regex.named_regex.replace("(?P<", "(?<"),
+    }
+}
+
+/// Like `normalizePagePath`, maps a page to the path of its files.
+fn normalize_page_path(page: &str) -> String {
+    if page == "/" {
+        "/index".to_string()
+    } else if page == "/index" || page.starts_with("/index/") {
+        format!("/index{page}")
+    } else {
+        page.to_string()
+    }
+}
+
+/// Like `buildDataRoute`, matches the `/_next/data/<build id>/<page>.json`
+/// requests of `page`.
+fn data_route(page: &str, build_id: &str) -> RoutesManifestDataRoute {
+    let page_path = normalize_page_path(page);
+    if is_dynamic_route(page) {
+        let named_route_regex =
+            get_named_route_regex(&format!("/_next/data/{build_id}{page_path}"));
+        let json_suffix =
+            |regex: &str| format!("{}\\.json$", regex.strip_suffix("(?:/)?$").unwrap_or(regex));
+        RoutesManifestDataRoute {
+            page: page.to_string(),
+            route_keys: Some(named_route_regex.route_keys),
+            data_route_regex: json_suffix(&named_route_regex.regex.regex),
+            named_data_route_regex: Some(json_suffix(
+                &named_route_regex.named_regex.replace("(?P<", "(?<"),
+            )),
+        }
+    } else {
+        RoutesManifestDataRoute {
+            page: page.to_string(),
+            route_keys: None,
+            data_route_regex: format!("^/_next/data/{}{page_path}.json$", regex::escape(build_id)),
+            named_data_route_regex: None,
+        }
+    }
+}
+
+/// Builds the `routes-manifest.json` the Next.js server uses to match
+/// requests to pages and custom routes.
+///
+/// Custom routes are emitted with their sources as-is, the server compiles
+/// them when loading the manifest. `data_pages` are the pages with
+/// `getStaticProps` or `getServerSideProps`, whose data is requested from
+/// `/_next/data/<build_id>`.
+#[turbo_tasks::function]
+pub async fn build_routes_manifest(
+    node_root: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+    rewrites: Vc<Rewrites>,
+    redirects: Vc<Redirects>,
+    headers: Vc<Headers>,
+    pages: Vc<Vec<String>>,
+    data_pages: Vc<Vec<String>>,
+    build_id: String,
+) -> Result<Vc<Box<dyn OutputAsset>>> {
+    let next_config = next_config.await?;
+    let pages = get_sorted_routes(&pages.await?)?;
+    let data_pages = get_sorted_routes(&data_pages.await?)?;
+
+    let routes_manifest = RoutesManifest {
+        version: 3,
+        pages404: true,
+        case_sensitive: false,
+        base_path: next_config.base_path.clone().unwrap_or_default(),
+        redirects: redirects.await?.clone_value(),
+        headers: headers.await?.clone_value(),
+        rewrites: rewrites.await?.clone_value(),
+        static_routes: pages
+            .iter()
+            .filter(|page| !is_dynamic_route(page) && !is_reserved_page(page))
+            .map(|page| manifest_route(page))
+            .collect(),
+        dynamic_routes: pages
+            .iter()
+            .filter(|page| is_dynamic_route(page))
+            .map(|page| manifest_route(page))
+            .collect(),
+        data_routes: data_pages
+            .iter()
+            .map(|page| data_route(page, &build_id))
+            .collect(),
+        i18n: next_config.i18n.clone(),
+        rsc: RscRoutesConfig::default(),
+        skip_middleware_url_normalize: next_config
+            .skip_middleware_url_normalize
+            .unwrap_or_default(),
+    };
+
+    Ok(Vc::upcast(VirtualOutputAsset::new(
+        node_root.join("routes-manifest.json".to_string()),
+        AssetContent::file(File::from(serde_json::to_string_pretty(&routes_manifest)?).into()),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::data_route;
+
+    #[test]
+    fn builds_static_data_routes() {
+        let route = data_route("/", "build-id");
+        assert_eq!(
+            route.data_route_regex,
+            "^/_next/data/build\\-id/index.json$"
+        );
+        assert!(route.route_keys.is_none());
+        assert!(route.named_data_route_regex.is_none());
+
+        let route = data_route("/blog", "build-id");
+        assert_eq!(route.data_route_regex, "^/_next/data/build\\-id/blog.json$");
+    }
+
+    #[test]
+    fn builds_dynamic_data_routes() {
+        let route = data_route("/blog/[slug]", "abc");
+        assert_eq!(
+            route.data_route_regex,
+            "^/_next/data/abc/blog/([^/]+?)\\.json$"
+        );
+        assert_eq!(
+            route.named_data_route_regex.as_deref(),
+            Some("^/_next/data/abc/blog/(?<slug>[^/]+?)\\.json$")
+        );
+        assert_eq!(
+            route.route_keys.unwrap().get("slug").map(String::as_str),
+            Some("slug")
+        );
+    }
+
+    #[test]
+    fn keeps_route_keys_in_segment_order() {
+        let route = data_route("/[lang]/[category]/[...slug]", "abc");
+        let route_keys = route.route_keys.unwrap();
+        assert_eq!(
+            route_keys.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["lang", "category", "slug"]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use turbo_tasks::{TryFlatJoinIterExt, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+use turbopack_binding::turbopack::core::{
+    asset::{Asset, AssetContent},
+    output::OutputAssets,
+    virtual_output::VirtualOutputAsset,
+};
+
+use crate::{
+    mode::NextMode,
+    next_config::{NextConfig, SriAlgorithm},
+};
+
+/// The subresource integrity of client scripts, keyed by their path relative
+/// to the client root like in the build manifests. This is also the format of
+/// `subresource-integrity-manifest.json`.
+#[turbo_tasks::value(transparent)]
+pub struct AssetsIntegrity(BTreeMap<String, String>);
+
+impl SriAlgorithm {
+    /// The value of the `integrity` attribute of a script with `content`.
+    pub fn integrity(self, content: &[u8]) -> String {
+        let (name, digest) = match self {
+            SriAlgorithm::Sha256 => ("sha256", Sha256::digest(content).to_vec()),
+            SriAlgorithm::Sha384 => ("sha384", Sha384::digest(content).to_vec()),
+            SriAlgorithm::Sha512 => ("sha512", Sha512::digest(content).to_vec()),
+        };
+        format!("{name}-{}", STANDARD.encode(digest))
+    }
+}
+
+/// Hashes the scripts among `assets` with the algorithm of
+/// `experimental.sri`. Like Next.js, only builds use subresource integrity.
+#[turbo_tasks::function]
+pub async fn client_assets_integrity(
+    client_relative_path: Vc<FileSystemPath>,
+    assets: Vc<OutputAssets>,
+    next_config: Vc<NextConfig>,
+    mode: NextMode,
+) -> Result<Vc<AssetsIntegrity>> {
+    let algorithm = next_config
+        .await?
+        .experimental
+        .sri
+        .as_ref()
+        .and_then(|sri| sri.algorithm);
+    let (Some(algorithm), NextMode::Build) = (algorithm, mode) else {
+        return Ok(Vc::cell(BTreeMap::new()));
+    };
+
+    let client_relative_path = &*client_relative_path.await?;
+    let integrity = assets
+        .await?
+        .iter()
+        .map(|&asset| async move {
+            let path = asset.ident().path().await?;
+            if path.extension_ref() != Some("js") {
+                return Ok(None);
+            }
+            let Some(path) = client_relative_path.get_path_to(&path) else {
+                return Ok(None);
+            };
+            let FileContent::Content(file) = &*asset.content().file_content().await? else {
+                return Ok(None);
+            };
+            Ok(Some((
+                path.to_string(),
+                algorithm.integrity(&file.content().to_bytes()?),
+            )))
+        })
+        .try_flat_join()
+        .await?;
+
+    Ok(Vc::cell(integrity.into_iter().collect()))
+}
+
+/// The partial `subresource-integrity-manifest.json` of an endpoint at `path`,
+/// which `next build` merges into the one read by the server. Nothing is
+/// emitted when subresource integrity isn't enabled.
+#[turbo_tasks::function]
+pub async fn subresource_integrity_manifest(
+    path: Vc<FileSystemPath>,
+    integrity: Vc<AssetsIntegrity>,
+) -> Result<Vc<OutputAssets>> {
+    let integrity = integrity.await?;
+    if integrity.is_empty() {
+        return Ok(Vc::cell(vec![]));
+    }
+    Ok(Vc::cell(vec![Vc::upcast(VirtualOutputAsset::new(
+        path,
+        AssetContent::file(File::from(serde_json::to_string_pretty(&*integrity)?).into()),
+    ))]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::next_config::SriAlgorithm;
+
+    #[test]
+    fn integrity_of_empty_content() {
+        assert_eq!(
+            SriAlgorithm::Sha256.integrity(b""),
+            "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+        );
+        assert_eq!(
+            SriAlgorithm::Sha384.integrity(b""),
+            "sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb"
+        );
+        assert_eq!(
+            SriAlgorithm::Sha512.integrity(b""),
+            "sha512-z4PhNX7vuL3xVChQ1m2AB9Yg5AULVxXcg/\
+             SpIdNs6c5H0NE8XYXysP+DGNKHfuwvY7kxvUdBeoGlODJ6+SfaPg=="
+        );
+    }
+}
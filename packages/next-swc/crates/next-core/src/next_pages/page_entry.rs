@@ -25,6 +25,7 @@ use turbopack_binding::{
 };
 
 use crate::{
+    mode::NextMode,
     next_config::NextConfig,
     next_edge::entry::wrap_edge_entry,
     pages_structure::{PagesStructure, PagesStructureItem},
@@ -42,6 +43,7 @@ pub async fn create_page_ssr_entry_module(
     pages_structure: Vc<PagesStructure>,
     runtime: NextRuntime,
     next_config: Vc<NextConfig>,
+    mode: NextMode,
 ) -> Result<Vc<Box<dyn EcmascriptChunkPlaceable>>> {
     let definition_page = &*next_original_name.await?;
     let definition_pathname = &*pathname.await?;
@@ -159,6 +161,7 @@ pub async fn create_page_ssr_entry_module(
                 Value::new(reference_type),
                 pages_structure,
                 next_config,
+                mode,
             );
         } else {
             ssr_module = wrap_edge_entry(
@@ -202,6 +205,7 @@ async fn wrap_edge_page(
     reference_type: Value<ReferenceType>,
     pages_structure: Vc<PagesStructure>,
     next_config: Vc<NextConfig>,
+    mode: NextMode,
 ) -> Result<Vc<Box<dyn Module>>> {
     const INNER: &str = "INNER_PAGE_ENTRY";
 
@@ -213,14 +217,14 @@ async fn wrap_edge_page(
 
     // TODO(WEB-1824): add build support
     let build_id = "development";
-    let dev = true;
+    let dev = mode == NextMode::Development;
 
     let sri_enabled = !dev
         && next_config
             .experimental
             .sri
             .as_ref()
-            .map(|sri| sri.algorithm.as_ref())
+            .and_then(|sri| sri.algorithm)
             .is_some();
 
     let source = load_next_js_template(
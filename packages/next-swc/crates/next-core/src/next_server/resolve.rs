@@ -3,6 +3,7 @@ use turbo_tasks::{Value, Vc};
 use turbopack_binding::{
     turbo::tasks_fs::{glob::Glob, FileJsonContent, FileSystemPath},
     turbopack::core::{
+        issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
         reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
         resolve::{
             find_context_file,
@@ -16,6 +17,8 @@ use turbopack_binding::{
     },
 };
 
+use crate::next_config::EsmExternalsMode;
+
 /// The predicated based on which the [ExternalCjsModulesResolvePlugin] decides
 /// whether to mark a module as external.
 #[turbo_tasks::value(into = "shared")]
@@ -35,7 +38,7 @@ pub(crate) struct ExternalCjsModulesResolvePlugin {
     project_path: Vc<FileSystemPath>,
     root: Vc<FileSystemPath>,
     predicate: Vc<ExternalPredicate>,
-    import_externals: bool,
+    esm_externals: Vc<EsmExternalsMode>,
 }
 
 #[turbo_tasks::value_impl]
@@ -45,13 +48,13 @@ impl ExternalCjsModulesResolvePlugin {
         project_path: Vc<FileSystemPath>,
         root: Vc<FileSystemPath>,
         predicate: Vc<ExternalPredicate>,
-        import_externals: bool,
+        esm_externals: Vc<EsmExternalsMode>,
     ) -> Vc<Self> {
         ExternalCjsModulesResolvePlugin {
             project_path,
             root,
             predicate,
-            import_externals,
+            esm_externals,
         }
         .cell()
     }
@@ -118,7 +121,8 @@ impl ResolvePlugin for ExternalCjsModulesResolvePlugin {
             }
         }
 
-        let is_esm = self.import_externals
+        let esm_externals = *self.esm_externals.await?;
+        let is_esm = esm_externals != EsmExternalsMode::Disabled
             && ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::Undefined)
                 .includes(&reference_type);
 
@@ -165,17 +169,25 @@ impl ResolvePlugin for ExternalCjsModulesResolvePlugin {
             Ok(FileType::Unsupported)
         }
 
-        let node_resolved = resolve(
-            context,
-            reference_type.clone(),
-            request,
-            if is_esm {
-                node_esm_resolve_options(context.root())
-            } else {
-                node_cjs_resolve_options(context.root())
-            },
-        );
-        let Some(result) = *node_resolved.first_source().await? else {
+        // Like node.js, imports prefer the `import` condition of package exports
+        // but fall back to the `require` one.
+        let resolve_options = if is_esm {
+            vec![
+                node_esm_resolve_options(context.root()),
+                node_cjs_resolve_options(context.root()),
+            ]
+        } else {
+            vec![node_cjs_resolve_options(context.root())]
+        };
+        let mut result = None;
+        for resolve_options in resolve_options {
+            let node_resolved = resolve(context, reference_type.clone(), request, resolve_options);
+            if let Some(source) = *node_resolved.first_source().await? {
+                result = Some(source);
+                break;
+            }
+        }
+        let Some(result) = result else {
             // this can't resolve with node.js, so bundle it
             return Ok(ResolveResultOption::none());
         };
@@ -188,28 +200,98 @@ impl ResolvePlugin for ExternalCjsModulesResolvePlugin {
                 Ok(ResolveResultOption::none())
             }
             (FileType::CommonJs, _) | (FileType::EcmaScriptModule, true) => {
-                if let Some(request) = request.await?.request() {
-                    // mark as external
-                    Ok(ResolveResultOption::some(
-                        ResolveResult::primary(ResolveResultItem::OriginalReferenceTypeExternal(
-                            request,
-                        ))
-                        .cell(),
-                    ))
-                } else {
-                    // unsupported request, bundle it
-                    Ok(ResolveResultOption::none())
-                }
+                external_result(request).await
+            }
+            (FileType::EcmaScriptModule, false) if esm_externals == EsmExternalsMode::Loose => {
+                // loose mode relies on the runtime to load required ES modules, so
+                // they are externalized like imported ones
+                external_result(request).await
             }
             (FileType::EcmaScriptModule, false) => {
                 // even with require() this resolves to a ESM,
                 // which would break node.js, bundle it
+                if esm_externals == EsmExternalsMode::Enabled {
+                    if let Some(request) = request_value.request() {
+                        ExternalEsmRequireIssue {
+                            file_path: context,
+                            request,
+                            resolved: path,
+                        }
+                        .cell()
+                        .emit();
+                    }
+                }
                 Ok(ResolveResultOption::none())
             }
         }
     }
 }
 
+/// Marks `request` as external, or bundles it if it can't be referenced by
+/// name at runtime.
+async fn external_result(request: Vc<Request>) -> Result<Vc<ResolveResultOption>> {
+    if let Some(request) = request.await?.request() {
+        // mark as external
+        Ok(ResolveResultOption::some(
+            ResolveResult::primary(ResolveResultItem::OriginalReferenceTypeExternal(request))
+                .cell(),
+        ))
+    } else {
+        // unsupported request, bundle it
+        Ok(ResolveResultOption::none())
+    }
+}
+
+/// An ES module in node_modules is required, so it can't be loaded
+/// externally and is bundled instead.
+#[turbo_tasks::value(shared)]
+struct ExternalEsmRequireIssue {
+    file_path: Vc<FileSystemPath>,
+    request: String,
+    resolved: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ExternalEsmRequireIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(format!(
+            "ESM packages ({}) need to be imported",
+            self.request
+        ))
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<OptionStyledString>> {
+        Ok(Vc::cell(Some(
+            StyledString::Text(format!(
+                "The package resolves to the ES module {}, which can't be loaded with require() \
+                 at runtime, so it has been bundled instead. Use 'import' to reference the \
+                 package, or set `experimental.esmExternals` to \"loose\" to load it externally \
+                 anyway.",
+                self.resolved.await?.path
+            ))
+            .cell(),
+        )))
+    }
+}
+
 // TODO move that to turbo
 #[turbo_tasks::value(transparent)]
 pub struct OptionGlob(Option<Vc<Glob>>);
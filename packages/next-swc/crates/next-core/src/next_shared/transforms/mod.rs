@@ -11,6 +11,7 @@ pub(crate) mod next_pure;
 pub(crate) mod next_react_server_components;
 pub(crate) mod next_shake_exports;
 pub(crate) mod next_strip_page_exports;
+pub(crate) mod optimize_package_imports;
 pub(crate) mod relay;
 pub(crate) mod server_actions;
 pub(crate) mod styled_components;
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use next_custom_transforms::transforms::named_import_transform::{named_import_transform, Config};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    swc::core::{
+        common::util::take::Take,
+        ecma::{ast::*, visit::FoldWith},
+    },
+    turbopack::{
+        ecmascript::{CustomTransformer, EcmascriptInputTransform, TransformContext},
+        turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+    },
+};
+
+use super::module_rule_match_js_no_url;
+
+/// Returns a rule which rewrites named imports of `packages` to
+/// `__barrel_optimize__` requests, which only import the used exports of the
+/// packages' barrel files. See [crate::next_barrel].
+pub fn get_next_optimize_package_imports_rule(
+    packages: Vec<String>,
+    enable_mdx_rs: bool,
+) -> ModuleRule {
+    let transformer =
+        EcmascriptInputTransform::Plugin(Vc::cell(Box::new(NextOptimizePackageImports {
+            config: Config { packages },
+        }) as _));
+    ModuleRule::new(
+        module_rule_match_js_no_url(enable_mdx_rs),
+        vec![ModuleRuleEffect::ExtendEcmascriptTransforms {
+            prepend: Vc::cell(vec![]),
+            append: Vc::cell(vec![transformer]),
+        }],
+    )
+}
+
+#[derive(Debug)]
+struct NextOptimizePackageImports {
+    config: Config,
+}
+
+#[async_trait]
+impl CustomTransformer for NextOptimizePackageImports {
+    async fn transform(&self, program: &mut Program, _ctx: &TransformContext<'_>) -> Result<()> {
+        let p = std::mem::replace(program, Program::Module(Module::dummy()));
+
+        *program = p.fold_with(&mut named_import_transform(self.config.clone()));
+        Ok(())
+    }
+}
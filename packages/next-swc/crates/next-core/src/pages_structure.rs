@@ -2,11 +2,12 @@ use anyhow::Result;
 use tracing::Instrument;
 use turbo_tasks::{Completion, ValueToString, Vc};
 use turbo_tasks_fs::FileSystemPathOption;
-use turbopack_binding::turbo::tasks_fs::{
-    DirectoryContent, DirectoryEntry, FileSystemEntryType, FileSystemPath,
-};
+use turbopack_binding::turbo::tasks_fs::{DirectoryContent, FileSystemEntryType, FileSystemPath};
 
-use crate::next_import_map::get_next_package;
+use crate::{
+    next_import_map::get_next_package,
+    util::{follow_symlink, FollowedEntry},
+};
 
 /// A final route in the pages directory.
 #[turbo_tasks::value]
@@ -197,8 +198,12 @@ async fn get_pages_structure_for_root_directory(
         let dir_content = project_path.read_dir().await?;
         if let DirectoryContent::Entries(entries) = &*dir_content {
             for (name, entry) in entries.iter() {
+                let Some(entry) = follow_symlink(*project_path, entry, Vc::cell(vec![])).await?
+                else {
+                    continue;
+                };
                 match entry {
-                    DirectoryEntry::File(file_project_path) => {
+                    FollowedEntry::File(file_project_path) => {
                         let Some(basename) = page_basename(name, page_extensions_raw) else {
                             continue;
                         };
@@ -207,7 +212,7 @@ async fn get_pages_structure_for_root_directory(
                                 let item_next_router_path =
                                     next_router_path.join("_app".to_string());
                                 app_item = Some(PagesStructureItem::new(
-                                    *file_project_path,
+                                    file_project_path,
                                     item_next_router_path,
                                     item_next_router_path,
                                 ));
@@ -216,7 +221,7 @@ async fn get_pages_structure_for_root_directory(
                                 let item_next_router_path =
                                     next_router_path.join("_document".to_string());
                                 document_item = Some(PagesStructureItem::new(
-                                    *file_project_path,
+                                    file_project_path,
                                     item_next_router_path,
                                     item_next_router_path,
                                 ));
@@ -225,7 +230,7 @@ async fn get_pages_structure_for_root_directory(
                                 let item_next_router_path =
                                     next_router_path.join("_error".to_string());
                                 error_item = Some(PagesStructureItem::new(
-                                    *file_project_path,
+                                    file_project_path,
                                     item_next_router_path,
                                     item_next_router_path,
                                 ));
@@ -238,7 +243,7 @@ async fn get_pages_structure_for_root_directory(
                                 items.push((
                                     basename,
                                     PagesStructureItem::new(
-                                        *file_project_path,
+                                        file_project_path,
                                         item_next_router_path,
                                         item_original_path,
                                     ),
@@ -246,28 +251,31 @@ async fn get_pages_structure_for_root_directory(
                             }
                         }
                     }
-                    DirectoryEntry::Directory(dir_project_path) => match name.as_ref() {
-                        "api" => {
-                            api_directory = Some(get_pages_structure_for_directory(
-                                *dir_project_path,
-                                next_router_path.join(name.clone()),
-                                1,
-                                page_extensions,
-                            ));
-                        }
-                        _ => {
-                            children.push((
-                                name,
-                                get_pages_structure_for_directory(
-                                    *dir_project_path,
+                    FollowedEntry::Directory(dir_project_path, symlinked_from) => {
+                        match name.as_ref() {
+                            "api" => {
+                                api_directory = Some(get_pages_structure_for_directory(
+                                    dir_project_path,
                                     next_router_path.join(name.clone()),
                                     1,
                                     page_extensions,
-                                ),
-                            ));
+                                    symlinked_from,
+                                ));
+                            }
+                            _ => {
+                                children.push((
+                                    name,
+                                    get_pages_structure_for_directory(
+                                        dir_project_path,
+                                        next_router_path.join(name.clone()),
+                                        1,
+                                        page_extensions,
+                                        symlinked_from,
+                                    ),
+                                ));
+                            }
                         }
-                    },
-                    _ => {}
+                    }
                 }
             }
         }
@@ -334,13 +342,16 @@ async fn get_pages_structure_for_root_directory(
 
 /// Handles a directory in the pages directory (or the pages directory itself).
 /// Calls itself recursively for sub directories or the
-/// [create_page_source_for_file] method for files.
+/// [create_page_source_for_file] method for files. `symlinked_from` are the
+/// directories whose symlinks were followed to reach `project_path`, see
+/// [follow_symlink].
 #[turbo_tasks::function]
 async fn get_pages_structure_for_directory(
     project_path: Vc<FileSystemPath>,
     next_router_path: Vc<FileSystemPath>,
     position: u32,
     page_extensions: Vc<Vec<String>>,
+    symlinked_from: Vc<Vec<String>>,
 ) -> Result<Vc<PagesDirectoryStructure>> {
     let span = {
         let path = project_path.to_string().await?;
@@ -354,8 +365,11 @@ async fn get_pages_structure_for_directory(
         let dir_content = project_path.read_dir().await?;
         if let DirectoryContent::Entries(entries) = &*dir_content {
             for (name, entry) in entries.iter() {
+                let Some(entry) = follow_symlink(project_path, entry, symlinked_from).await? else {
+                    continue;
+                };
                 match entry {
-                    DirectoryEntry::File(file_project_path) => {
+                    FollowedEntry::File(file_project_path) => {
                         let Some(basename) = page_basename(name, page_extensions_raw) else {
                             continue;
                         };
@@ -367,24 +381,24 @@ async fn get_pages_structure_for_directory(
                         items.push((
                             basename,
                             PagesStructureItem::new(
-                                *file_project_path,
+                                file_project_path,
                                 item_next_router_path,
                                 item_original_name,
                             ),
                         ));
                     }
-                    DirectoryEntry::Directory(dir_project_path) => {
+                    FollowedEntry::Directory(dir_project_path, symlinked_from) => {
                         children.push((
                             name,
                             get_pages_structure_for_directory(
-                                *dir_project_path,
+                                dir_project_path,
                                 next_router_path.join(name.clone()),
                                 position + 1,
                                 page_extensions,
+                                symlinked_from,
                             ),
                         ));
                     }
-                    _ => {}
                 }
             }
         }
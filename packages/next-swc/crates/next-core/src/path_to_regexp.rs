@@ -0,0 +1,376 @@
+//! A port of the subset of [path-to-regexp](https://github.com/pillarjs/path-to-regexp)
+//! v6 Next.js uses to compile custom route and middleware matcher sources.
+
+use std::iter::Peekable;
+
+use anyhow::{bail, Result};
+
+#[derive(Debug, PartialEq)]
+enum LexToken {
+    Open,
+    Close,
+    Pattern(String),
+    Name(String),
+    Char(char),
+    EscapedChar(char),
+    Modifier(char),
+    End,
+}
+
+fn lexer(source: &str) -> Result<Vec<LexToken>> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            c @ ('*' | '+' | '?') => {
+                tokens.push(LexToken::Modifier(c));
+                i += 1;
+            }
+            '\\' => {
+                let Some(&c) = chars.get(i + 1) else {
+                    bail!("Unexpected end of input after \"\\\" at {i} in {source:?}");
+                };
+                tokens.push(LexToken::EscapedChar(c));
+                i += 2;
+            }
+            '{' => {
+                tokens.push(LexToken::Open);
+                i += 1;
+            }
+            '}' => {
+                tokens.push(LexToken::Close);
+                i += 1;
+            }
+            ':' => {
+                let name = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+                    .collect::<String>();
+                if name.is_empty() {
+                    bail!("Missing parameter name at {i} in {source:?}");
+                }
+                i += 1 + name.len();
+                tokens.push(LexToken::Name(name));
+            }
+            '(' => {
+                let mut count = 1;
+                let mut pattern = String::new();
+                let mut j = i + 1;
+                if chars.get(j) == Some(&'?') {
+                    bail!("Pattern cannot start with \"?\" at {j} in {source:?}");
+                }
+                while j < chars.len() {
+                    match chars[j] {
+                        '\\' => {
+                            pattern.extend(chars[j..].iter().take(2));
+                            j += 2;
+                            continue;
+                        }
+                        ')' => {
+                            count -= 1;
+                            if count == 0 {
+                                j += 1;
+                                break;
+                            }
+                        }
+                        '(' => {
+                            count += 1;
+                            if chars.get(j + 1) != Some(&'?') {
+                                bail!("Capturing groups are not allowed at {j} in {source:?}");
+                            }
+                        }
+                        _ => {}
+                    }
+                    pattern.push(chars[j]);
+                    j += 1;
+                }
+                if count != 0 {
+                    bail!("Unbalanced pattern at {i} in {source:?}");
+                }
+                if pattern.is_empty() {
+                    bail!("Missing pattern at {i} in {source:?}");
+                }
+                tokens.push(LexToken::Pattern(pattern));
+                i = j;
+            }
+            c => {
+                tokens.push(LexToken::Char(c));
+                i += 1;
+            }
+        }
+    }
+    tokens.push(LexToken::End);
+    Ok(tokens)
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Token {
+    Path(String),
+    Key {
+        name: Option<String>,
+        prefix: String,
+        suffix: String,
+        pattern: String,
+        modifier: Option<char>,
+    },
+}
+
+struct Parser {
+    tokens: Peekable<std::vec::IntoIter<LexToken>>,
+}
+
+impl Parser {
+    fn try_consume<T>(&mut self, f: impl Fn(&LexToken) -> Option<T>) -> Option<T> {
+        let value = f(self.tokens.peek()?)?;
+        self.tokens.next();
+        Some(value)
+    }
+
+    fn try_char(&mut self) -> Option<char> {
+        self.try_consume(|token| match token {
+            LexToken::Char(c) => Some(*c),
+            _ => None,
+        })
+    }
+
+    fn try_escaped_char(&mut self) -> Option<char> {
+        self.try_consume(|token| match token {
+            LexToken::EscapedChar(c) => Some(*c),
+            _ => None,
+        })
+    }
+
+    fn try_name(&mut self) -> Option<String> {
+        self.try_consume(|token| match token {
+            LexToken::Name(name) => Some(name.clone()),
+            _ => None,
+        })
+    }
+
+    fn try_pattern(&mut self) -> Option<String> {
+        self.try_consume(|token| match token {
+            LexToken::Pattern(pattern) => Some(pattern.clone()),
+            _ => None,
+        })
+    }
+
+    fn try_modifier(&mut self) -> Option<char> {
+        self.try_consume(|token| match token {
+            LexToken::Modifier(c) => Some(*c),
+            _ => None,
+        })
+    }
+
+    fn try_open(&mut self) -> bool {
+        self.try_consume(|token| (*token == LexToken::Open).then_some(()))
+            .is_some()
+    }
+
+    fn consume_text(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.try_char().or_else(|| self.try_escaped_char()) {
+            text.push(c);
+        }
+        text
+    }
+
+    fn must_consume(&mut self, expected: LexToken, source: &str) -> Result<()> {
+        match self.tokens.next() {
+            Some(token) if token == expected => Ok(()),
+            token => bail!("Unexpected {token:?}, expected {expected:?} in {source:?}"),
+        }
+    }
+}
+
+pub(crate) const DEFAULT_PATTERN: &str = "[^\\/#\\?]+?";
+
+/// Parses a path-to-regexp `source` into its static and parameter tokens.
+pub(crate) fn parse(source: &str) -> Result<Vec<Token>> {
+    let mut parser = Parser {
+        tokens: lexer(source)?.into_iter().peekable(),
+    };
+    let mut result = vec![];
+    let mut path = String::new();
+    loop {
+        let char = parser.try_char();
+        let name = parser.try_name();
+        let pattern = parser.try_pattern();
+        if name.is_some() || pattern.is_some() {
+            let mut prefix = char.map(String::from).unwrap_or_default();
+            if !matches!(prefix.as_str(), "" | "." | "/") {
+                path.push_str(&prefix);
+                prefix.clear();
+            }
+            if !path.is_empty() {
+                result.push(Token::Path(std::mem::take(&mut path)));
+            }
+            result.push(Token::Key {
+                name,
+                prefix,
+                suffix: String::new(),
+                pattern: pattern.unwrap_or_else(|| DEFAULT_PATTERN.to_string()),
+                modifier: parser.try_modifier(),
+            });
+            continue;
+        }
+
+        if let Some(c) = char.or_else(|| parser.try_escaped_char()) {
+            path.push(c);
+            continue;
+        }
+
+        if !path.is_empty() {
+            result.push(Token::Path(std::mem::take(&mut path)));
+        }
+
+        if parser.try_open() {
+            let prefix = parser.consume_text();
+            let name = parser.try_name();
+            let pattern = parser.try_pattern();
+            let suffix = parser.consume_text();
+            parser.must_consume(LexToken::Close, source)?;
+            result.push(Token::Key {
+                pattern: match (&name, pattern) {
+                    (_, Some(pattern)) => pattern,
+                    (Some(_), None) => DEFAULT_PATTERN.to_string(),
+                    (None, None) => String::new(),
+                },
+                name,
+                prefix,
+                suffix,
+                modifier: parser.try_modifier(),
+            });
+            continue;
+        }
+
+        parser.must_consume(LexToken::End, source)?;
+        return Ok(result);
+    }
+}
+
+fn escape_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if ".+*?=^!:${}()[]|/\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Compiles a path-to-regexp `source` into the source of an equivalent regular
+/// expression, like Next.js' `tryToParsePath(source).regexStr` does.
+pub fn path_to_regexp(source: &str) -> Result<String> {
+    let mut route = "^".to_string();
+    for token in parse(source)? {
+        match token {
+            Token::Path(path) => route.push_str(&escape_string(&path)),
+            Token::Key {
+                name: _,
+                prefix,
+                suffix,
+                pattern,
+                modifier,
+            } => {
+                let prefix = escape_string(&prefix);
+                let suffix = escape_string(&suffix);
+                let modifier = modifier.map(String::from).unwrap_or_default();
+                let repeated = matches!(modifier.as_str(), "*" | "+");
+                if pattern.is_empty() {
+                    route.push_str(&format!("(?:{prefix}{suffix}){modifier}"));
+                } else if prefix.is_empty() && suffix.is_empty() {
+                    if repeated {
+                        route.push_str(&format!("((?:{pattern}){modifier})"));
+                    } else {
+                        route.push_str(&format!("({pattern}){modifier}"));
+                    }
+                } else if repeated {
+                    let optional = if modifier == "*" { "?" } else { "" };
+                    route.push_str(&format!(
+                        "(?:{prefix}((?:{pattern})(?:{suffix}{prefix}(?:{pattern}))*\
+                         ){suffix}){optional}"
+                    ));
+                } else {
+                    route.push_str(&format!("(?:{prefix}({pattern}){suffix}){modifier}"));
+                }
+            }
+        }
+    }
+    route.push_str("[\\/#\\?]?$");
+    Ok(escape_slashes(&route))
+}
+
+/// Escapes forward slashes outside of character classes, as
+/// `RegExp.prototype.source` does.
+fn escape_slashes(source: &str) -> String {
+    let mut escaped = String::with_capacity(source.len());
+    let mut in_class = false;
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                escaped.push(c);
+                escaped.extend(chars.next());
+                continue;
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => escaped.push('\\'),
+            _ => {}
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::path_to_regexp;
+
+    #[test]
+    fn compiles_static_paths() {
+        assert_eq!(path_to_regexp("/about").unwrap(), "^\\/about[\\/#\\?]?$");
+        assert_eq!(path_to_regexp("/").unwrap(), "^\\/[\\/#\\?]?$");
+    }
+
+    #[test]
+    fn compiles_parameters() {
+        assert_eq!(
+            path_to_regexp("/blog/:slug").unwrap(),
+            "^\\/blog(?:\\/([^\\/#\\?]+?))[\\/#\\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp("/docs/:path*").unwrap(),
+            "^\\/docs(?:\\/((?:[^\\/#\\?]+?)(?:\\/(?:[^\\/#\\?]+?))*))?[\\/#\\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp("/docs/:path+").unwrap(),
+            "^\\/docs(?:\\/((?:[^\\/#\\?]+?)(?:\\/(?:[^\\/#\\?]+?))*))[\\/#\\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp("/:locale?/about").unwrap(),
+            "^(?:\\/([^\\/#\\?]+?))?\\/about[\\/#\\?]?$"
+        );
+    }
+
+    #[test]
+    fn compiles_patterns() {
+        assert_eq!(
+            path_to_regexp("/((?!api|_next).*)").unwrap(),
+            "^(?:\\/((?!api|_next).*))[\\/#\\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp("/:nextData(_next/data/[^/]{1,})?/about(.json)?").unwrap(),
+            "^(?:\\/(_next\\/data\\/[^/]{1,}))?\\/about(.json)?[\\/#\\?]?$"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_sources() {
+        assert!(path_to_regexp("/:").is_err());
+        assert!(path_to_regexp("/(a(b))").is_err());
+        assert!(path_to_regexp("/(").is_err());
+    }
+}
@@ -0,0 +1,395 @@
+//! Generates the `link.d.ts` declaration for `experimental.typedRoutes`, which
+//! types the `href` of `next/link` and the `next/navigation` router methods
+//! with the routes of the project.
+
+use anyhow::Result;
+use indexmap::IndexSet;
+use indoc::formatdoc;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_binding::turbopack::core::{
+    asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+};
+
+use crate::{
+    app_structure::{Entrypoint, Entrypoints},
+    next_config::{NextConfig, Rewrites},
+    pages_structure::{PagesDirectoryStructure, PagesStructure, PagesStructureItem},
+    path_to_regexp::{parse, Token, DEFAULT_PATTERN},
+};
+
+/// Returns the routes of the pages and route handlers in the app directory.
+#[turbo_tasks::function]
+pub async fn app_typed_routes(entrypoints: Vc<Entrypoints>) -> Result<Vc<Vec<String>>> {
+    Ok(Vc::cell(
+        entrypoints
+            .await?
+            .iter()
+            .filter(|(_, entrypoint)| {
+                matches!(
+                    entrypoint,
+                    Entrypoint::AppPage { .. } | Entrypoint::AppRoute { .. }
+                )
+            })
+            .map(|(app_path, _)| app_path.to_string())
+            // The not found page can't be navigated to.
+            .filter(|route| !matches!(route.as_str(), "/_not-found" | "/not-found"))
+            .collect(),
+    ))
+}
+
+/// Returns the routes of the pages and API routes in the pages directory.
+#[turbo_tasks::function]
+pub async fn pages_typed_routes(pages_structure: Vc<PagesStructure>) -> Result<Vc<Vec<String>>> {
+    let PagesStructure { api, pages, .. } = &*pages_structure.await?;
+
+    let mut routes = vec![];
+    let mut queue = api.iter().chain(pages.iter()).copied().collect::<Vec<_>>();
+    while let Some(dir) = queue.pop() {
+        let PagesDirectoryStructure {
+            ref items,
+            ref children,
+            ..
+        } = *dir.await?;
+        for &item in items.iter() {
+            let PagesStructureItem {
+                next_router_path, ..
+            } = *item.await?;
+            let route = format!("/{}", next_router_path.await?.path);
+            // Error pages can't be navigated to.
+            if !matches!(route.as_str(), "/404" | "/500") {
+                routes.push(route);
+            }
+        }
+        queue.extend(children.iter().copied());
+    }
+
+    Ok(Vc::cell(routes))
+}
+
+/// Returns the routes which can be navigated to because of the configured
+/// rewrites and redirects.
+#[turbo_tasks::function]
+pub async fn custom_typed_routes(next_config: Vc<NextConfig>) -> Result<Vc<Vec<String>>> {
+    let next_config = next_config.await?;
+
+    let mut routes = vec![];
+    if let Some(Rewrites {
+        before_files,
+        after_files,
+        fallback,
+    }) = &next_config.original_rewrites
+    {
+        for rewrite in before_files.iter().chain(after_files).chain(fallback) {
+            routes.extend(custom_route_to_routes(&rewrite.source));
+        }
+    }
+    for redirect in next_config.original_redirects.iter().flatten() {
+        routes.extend(custom_route_to_routes(&redirect.source));
+    }
+
+    Ok(Vc::cell(routes))
+}
+
+/// Converts the path-to-regexp `source` of a custom route to the routes it
+/// matches, in the file system notation of [route_type].
+///
+/// Optional parameters and static parts fork the route into a copy without and
+/// one with them. Returns no routes for sources with regular expressions,
+/// which can't be expressed in the declaration.
+fn custom_route_to_routes(source: &str) -> Vec<String> {
+    // Invalid sources are reported when the custom routes are loaded.
+    let Ok(tokens) = parse(source) else {
+        return vec![];
+    };
+
+    let mut routes = vec![String::new()];
+    for token in tokens {
+        match token {
+            Token::Path(path) => routes.iter_mut().for_each(|route| route.push_str(&path)),
+            Token::Key {
+                name,
+                prefix,
+                suffix: _,
+                pattern,
+                modifier,
+            } => {
+                let slug = name.unwrap_or_else(|| "slug".to_string());
+                let segment = match (modifier, pattern.as_str()) {
+                    (Some('*'), _) | (None, ".*") => format!("{prefix}[[...{slug}]]"),
+                    (Some('+'), _) | (None, ".+") => format!("{prefix}[...{slug}]"),
+                    (None, DEFAULT_PATTERN) => format!("{prefix}[{slug}]"),
+                    (Some('?'), pattern) => {
+                        let optional = if pattern == DEFAULT_PATTERN {
+                            format!("{prefix}[{slug}]")
+                        } else if pattern
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '/')
+                        {
+                            format!("{prefix}{pattern}")
+                        } else {
+                            return vec![];
+                        };
+                        let forked = routes
+                            .iter()
+                            .map(|route| format!("{route}{optional}"))
+                            .collect::<Vec<_>>();
+                        routes.extend(forked);
+                        continue;
+                    }
+                    _ => return vec![],
+                };
+                routes.iter_mut().for_each(|route| route.push_str(&segment));
+            }
+        }
+    }
+    routes
+        .into_iter()
+        .map(|route| {
+            if route.is_empty() {
+                "/".to_string()
+            } else {
+                route
+            }
+        })
+        .collect()
+}
+
+/// Converts a route in the file system notation to a TypeScript template
+/// literal type, with the dynamic segments parsed like `build_path_regex`
+/// does for [crate::next_route_matcher].
+///
+/// Returns whether the route is dynamic as well.
+fn route_type(route: &str) -> (bool, String) {
+    let mut is_dynamic = false;
+    let segments = route
+        .split('/')
+        .map(|segment| {
+            let slug = if let Some(segment) = segment.strip_prefix("[[...") {
+                segment
+                    .split_once("]]")
+                    .map(|(_, rem)| ("OptionalCatchAllSlug", rem))
+            } else if let Some(segment) = segment.strip_prefix("[...") {
+                segment
+                    .split_once(']')
+                    .map(|(_, rem)| ("CatchAllSlug", rem))
+            } else if let Some(segment) = segment.strip_prefix('[') {
+                segment.split_once(']').map(|(_, rem)| ("SafeSlug", rem))
+            } else {
+                None
+            };
+            match slug {
+                Some((slug, rem)) => {
+                    is_dynamic = true;
+                    format!("${{{slug}<T>}}{}", escape_template_literal(rem))
+                }
+                None => escape_template_literal(segment),
+            }
+        })
+        .collect::<Vec<_>>();
+    (is_dynamic, format!("`{}`", segments.join("/")))
+}
+
+fn escape_template_literal(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
+
+/// Builds the `types/link.d.ts` declaration for `experimental.typedRoutes`
+/// from the given routes, like the `NextTypesPlugin` of webpack does.
+#[turbo_tasks::function]
+pub async fn build_typed_routes_declaration(
+    node_root: Vc<FileSystemPath>,
+    routes: Vc<Vec<String>>,
+) -> Result<Vc<Box<dyn OutputAsset>>> {
+    let mut static_routes = IndexSet::new();
+    let mut dynamic_routes = IndexSet::new();
+    for route in routes.await?.iter() {
+        let (is_dynamic, route_type) = route_type(route);
+        if is_dynamic {
+            dynamic_routes.insert(route_type);
+        } else {
+            static_routes.insert(route_type);
+        }
+    }
+
+    let union = |route_types: IndexSet<String>| {
+        if route_types.is_empty() {
+            "never".to_string()
+        } else {
+            route_types
+                .iter()
+                .map(|route_type| format!("\n    | {route_type}"))
+                .collect()
+        }
+    };
+    // Without any routes, any string is accepted.
+    let route_impl = if static_routes.is_empty() && dynamic_routes.is_empty() {
+        "string"
+    } else {
+        "
+    | StaticRoutes
+    | SearchOrHash
+    | WithProtocol
+    | `${StaticRoutes}${SearchOrHash}`
+    | (T extends `${DynamicRoutes<infer _>}${Suffix}` ? T : never)"
+    };
+    let static_routes = union(static_routes);
+    let dynamic_routes = union(dynamic_routes);
+
+    let declaration = formatdoc! {
+        r#"
+            // Type definitions for Next.js routes
+
+            /**
+             * Internal types used by the Next.js router and Link component.
+             * These types are not meant to be used directly.
+             * @internal
+             */
+            declare namespace __next_route_internal_types__ {{
+              type SearchOrHash = `?${{string}}` | `#${{string}}`
+              type WithProtocol = `${{string}}:${{string}}`
+
+              type Suffix = '' | SearchOrHash
+
+              type SafeSlug<S extends string> = S extends `${{string}}/${{string}}`
+                ? never
+                : S extends `${{string}}${{SearchOrHash}}`
+                ? never
+                : S extends ''
+                ? never
+                : S
+
+              type CatchAllSlug<S extends string> = S extends `${{string}}${{SearchOrHash}}`
+                ? never
+                : S extends ''
+                ? never
+                : S
+
+              type OptionalCatchAllSlug<S extends string> =
+                S extends `${{string}}${{SearchOrHash}}` ? never : S
+
+              type StaticRoutes = {static_routes}
+              type DynamicRoutes<T extends string = string> = {dynamic_routes}
+
+              type RouteImpl<T> = {route_impl}
+            }}
+
+            declare module 'next' {{
+              export {{ default }} from 'next/types/index.js'
+              export * from 'next/types/index.js'
+
+              export type Route<T extends string = string> =
+                __next_route_internal_types__.RouteImpl<T>
+            }}
+
+            declare module 'next/link' {{
+              import type {{ LinkProps as OriginalLinkProps }} from 'next/dist/client/link.js'
+              import type {{ AnchorHTMLAttributes, DetailedHTMLProps }} from 'react'
+              import type {{ UrlObject }} from 'url'
+
+              type LinkRestProps = Omit<
+                Omit<
+                  DetailedHTMLProps<
+                    AnchorHTMLAttributes<HTMLAnchorElement>,
+                    HTMLAnchorElement
+                  >,
+                  keyof OriginalLinkProps
+                > &
+                  OriginalLinkProps,
+                'href'
+              >
+
+              export type LinkProps<RouteInferType> = LinkRestProps & {{
+                /**
+                 * The path or URL to navigate to. This is the only required prop. It can also be an object.
+                 * @see https://nextjs.org/docs/api-reference/next/link
+                 */
+                href: __next_route_internal_types__.RouteImpl<RouteInferType> | UrlObject
+              }}
+
+              export default function Link<RouteType>(props: LinkProps<RouteType>): JSX.Element
+            }}
+
+            declare module 'next/navigation' {{
+              export * from 'next/dist/client/components/navigation.js'
+
+              import type {{ NavigateOptions, AppRouterInstance as OriginalAppRouterInstance }} from 'next/dist/shared/lib/app-router-context.shared-runtime.js'
+              interface AppRouterInstance extends OriginalAppRouterInstance {{
+                /**
+                 * Navigate to the provided href.
+                 * Pushes a new history entry.
+                 */
+                push<RouteType>(href: __next_route_internal_types__.RouteImpl<RouteType>, options?: NavigateOptions): void
+                /**
+                 * Navigate to the provided href.
+                 * Replaces the current history entry.
+                 */
+                replace<RouteType>(href: __next_route_internal_types__.RouteImpl<RouteType>, options?: NavigateOptions): void
+                /**
+                 * Prefetch the provided href.
+                 */
+                prefetch<RouteType>(href: __next_route_internal_types__.RouteImpl<RouteType>): void
+              }}
+
+              export declare function useRouter(): AppRouterInstance;
+            }}
+        "#
+    };
+
+    Ok(Vc::upcast(VirtualOutputAsset::new(
+        node_root.join("types/link.d.ts".to_string()),
+        AssetContent::file(File::from(declaration).into()),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{custom_route_to_routes, route_type};
+
+    #[test]
+    fn formats_route_types() {
+        assert_eq!(route_type("/"), (false, "`/`".to_string()));
+        assert_eq!(
+            route_type("/blog/about"),
+            (false, "`/blog/about`".to_string())
+        );
+        assert_eq!(
+            route_type("/blog/[slug]"),
+            (true, "`/blog/${SafeSlug<T>}`".to_string())
+        );
+        assert_eq!(
+            route_type("/docs/[...slug]"),
+            (true, "`/docs/${CatchAllSlug<T>}`".to_string())
+        );
+        assert_eq!(
+            route_type("/shop/[[...slug]]"),
+            (true, "`/shop/${OptionalCatchAllSlug<T>}`".to_string())
+        );
+    }
+
+    #[test]
+    fn converts_custom_routes() {
+        assert_eq!(custom_route_to_routes("/about"), vec!["/about"]);
+        assert_eq!(custom_route_to_routes("/blog/:slug"), vec!["/blog/[slug]"]);
+        assert_eq!(
+            custom_route_to_routes("/docs/:path*"),
+            vec!["/docs/[[...path]]"]
+        );
+        assert_eq!(
+            custom_route_to_routes("/docs/:path+"),
+            vec!["/docs/[...path]"]
+        );
+        assert_eq!(
+            custom_route_to_routes("/docs/(v1)?"),
+            vec!["/docs", "/docs/v1"]
+        );
+        assert_eq!(
+            custom_route_to_routes("/:locale?/about"),
+            vec!["/about", "/[locale]/about"]
+        );
+        assert_eq!(custom_route_to_routes("/:locale?"), vec!["/", "/[locale]"]);
+        assert!(custom_route_to_routes("/post/:id(\\d+)").is_empty());
+    }
+}
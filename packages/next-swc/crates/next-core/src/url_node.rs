@@ -13,7 +13,11 @@ pub enum UrlNodeError {
     ExtraBrackets(String),
     #[error("Segment names may not start with erroneous periods ('{0}').")]
     ErroneousPeriod(String),
-    #[error("You cannot use different slug names for the same dynamic path ('{0}' !== '{1}').")]
+    #[error(
+        "You cannot use different slug names for the same dynamic path ('{0}' !== '{1}'). Dynamic \
+         segments at the same level, like `[{0}]` and `[{1}]`, match the same URLs, so they must \
+         use the same slug name."
+    )]
     DifferentSlugNames(String, String),
     #[error("You cannot have the same slug name \"{0}\" repeat within a single dynamic path.")]
     RepeatingSlugName(String),
@@ -235,8 +239,6 @@ impl UrlNode {
                     // Because currently multiple dynamic params on the same segment level are not
                     // supported
                     if previous_slug != &next_slug {
-                        // TODO: This error seems to be confusing for users, needs an error link,
-                        // the description can be based on above comment.
                         return Err(UrlNodeError::DifferentSlugNames(
                             previous_slug.to_string(),
                             next_slug.to_string(),
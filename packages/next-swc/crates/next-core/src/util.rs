@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use anyhow::{bail, Context, Result};
 use indexmap::{IndexMap, IndexSet};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -8,7 +10,10 @@ use swc_core::{
 use turbo_tasks::{trace::TraceRawVcs, TaskInput, ValueDefault, ValueToString, Vc};
 use turbo_tasks_fs::{rope::Rope, util::join_path, File};
 use turbopack_binding::{
-    turbo::tasks_fs::{json::parse_json_rope_with_source_context, FileContent, FileSystemPath},
+    turbo::tasks_fs::{
+        json::parse_json_rope_with_source_context, DirectoryEntry, FileContent,
+        FileSystemEntryType, FileSystemPath, LinkContent, LinkType,
+    },
     turbopack::{
         core::{
             asset::AssetContent,
@@ -19,7 +24,7 @@ use turbopack_binding::{
             virtual_source::VirtualSource,
         },
         ecmascript::{
-            analyzer::{JsValue, ObjectPart},
+            analyzer::{ConstantNumber, ConstantValue, JsValue, ObjectPart},
             parse::ParseResult,
             utils::StringifyJs,
             EcmascriptModuleAsset,
@@ -28,7 +33,10 @@ use turbopack_binding::{
     },
 };
 
-use crate::{next_config::NextConfig, next_import_map::get_next_package};
+use crate::{
+    next_config::{NextConfig, RouteHas},
+    next_import_map::get_next_package,
+};
 
 const NEXT_TEMPLATE_PATH: &str = "dist/esm/build/templates";
 
@@ -148,7 +156,25 @@ pub struct NextSourceConfig {
     pub runtime: NextRuntime,
 
     /// Middleware router matchers
-    pub matcher: Option<Vec<String>>,
+    pub matcher: Option<Vec<MiddlewareMatcherKind>>,
+}
+
+/// A matcher of a middleware's `config.matcher`, either just a path or an
+/// object with conditions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+pub enum MiddlewareMatcherKind {
+    Str(String),
+    Matcher(MiddlewareMatcherConfig),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct MiddlewareMatcherConfig {
+    pub source: String,
+    pub locale: Option<bool>,
+    pub has: Option<Vec<RouteHas>>,
+    pub missing: Option<Vec<RouteHas>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -359,36 +385,23 @@ fn parse_config_from_js_value(module: Vc<Box<dyn Module>>, value: &JsValue) -> N
                             }
                         }
                         if key == "matcher" {
+                            let items: Vec<&JsValue> = match value {
+                                JsValue::Array { items, .. } => items.iter().collect(),
+                                value => vec![value],
+                            };
                             let mut matchers = vec![];
-                            match value {
-                                JsValue::Constant(matcher) => {
-                                    if let Some(matcher) = matcher.as_str() {
-                                        matchers.push(matcher.to_string());
-                                    } else {
-                                        invalid_config(
-                                            "The matcher property must be a string or array of \
-                                             strings",
-                                            value,
-                                        );
-                                    }
-                                }
-                                JsValue::Array { items, .. } => {
-                                    for item in items {
-                                        if let Some(matcher) = item.as_str() {
-                                            matchers.push(matcher.to_string());
-                                        } else {
-                                            invalid_config(
-                                                "The matcher property must be a string or array \
-                                                 of strings",
-                                                value,
-                                            );
-                                        }
-                                    }
+                            for item in items {
+                                match js_value_to_json(item).and_then(|item| {
+                                    serde_json::from_value::<MiddlewareMatcherKind>(item).ok()
+                                }) {
+                                    Some(matcher) => matchers.push(matcher),
+                                    None => invalid_config(
+                                        "The matcher property must be a string, an object with a \
+                                         `source` and optional `locale`, `has` and `missing` \
+                                         properties, or an array of those.",
+                                        value,
+                                    ),
                                 }
-                                _ => invalid_config(
-                                    "The matcher property must be a string or array of strings",
-                                    value,
-                                ),
                             }
                             config.matcher = Some(matchers);
                         }
@@ -411,6 +424,34 @@ fn parse_config_from_js_value(module: Vc<Box<dyn Module>>, value: &JsValue) -> N
     config
 }
 
+/// Converts a statically evaluated value to JSON, if it only consists of
+/// constants, arrays and plain objects.
+fn js_value_to_json(value: &JsValue) -> Option<serde_json::Value> {
+    Some(match value {
+        JsValue::Constant(ConstantValue::Str(str)) => str.as_str().into(),
+        JsValue::Constant(ConstantValue::Num(ConstantNumber(num))) => (*num).into(),
+        JsValue::Constant(ConstantValue::True) => true.into(),
+        JsValue::Constant(ConstantValue::False) => false.into(),
+        JsValue::Constant(ConstantValue::Null) => serde_json::Value::Null,
+        JsValue::Array { items, .. } => items
+            .iter()
+            .map(js_value_to_json)
+            .collect::<Option<Vec<_>>>()?
+            .into(),
+        JsValue::Object { parts, .. } => {
+            let mut object = serde_json::Map::new();
+            for part in parts {
+                let ObjectPart::KeyValue(key, value) = part else {
+                    return None;
+                };
+                object.insert(key.as_str()?.to_string(), js_value_to_json(value)?);
+            }
+            object.into()
+        }
+        _ => return None,
+    })
+}
+
 /// Loads a next.js template, replaces `replacements` and `injections` and makes
 /// sure there are none left over.
 pub async fn load_next_js_template(
@@ -702,3 +743,186 @@ pub async fn load_next_js_templateon<T: DeserializeOwned>(
 
     Ok(result)
 }
+
+/// A directory entry of the app or pages directory, with symlinks followed to
+/// their target.
+pub enum FollowedEntry {
+    File(Vc<FileSystemPath>),
+    /// A directory, and the `symlinked_from` to traverse it with.
+    Directory(Vc<FileSystemPath>, Vc<Vec<String>>),
+}
+
+/// Follows `entry` of `dir` if it's a symlink. Returns `None` for entries which
+/// are neither files nor directories, and for symlinks which can't be
+/// followed.
+///
+/// `symlinked_from` are the paths of the directories whose symlinks were
+/// followed to reach `dir`. A symlink to a directory containing `dir` or one of
+/// those is a cycle, and is reported instead of being followed.
+pub async fn follow_symlink(
+    dir: Vc<FileSystemPath>,
+    entry: &DirectoryEntry,
+    symlinked_from: Vc<Vec<String>>,
+) -> Result<Option<FollowedEntry>> {
+    let link = match *entry {
+        DirectoryEntry::File(file) => return Ok(Some(FollowedEntry::File(file))),
+        DirectoryEntry::Directory(directory) => {
+            return Ok(Some(FollowedEntry::Directory(directory, symlinked_from)))
+        }
+        DirectoryEntry::Symlink(link) => link,
+        _ => return Ok(None),
+    };
+
+    Ok(match *resolve_symlink(link).await? {
+        SymlinkTarget::File(file) => Some(FollowedEntry::File(file)),
+        SymlinkTarget::Directory(target) => {
+            let target_path = &target.await?.path;
+            let mut symlinked_from = symlinked_from.await?.clone_value();
+            symlinked_from.push(dir.await?.path.clone());
+
+            if symlinked_from
+                .iter()
+                .any(|path| is_inside_or_equal(path, target_path))
+            {
+                SymlinkIssue {
+                    path: link,
+                    message: StyledString::Text(format!(
+                        "The symlink points to {}, which contains it. It is not followed, as that \
+                         would be a cycle.",
+                        target.to_string().await?
+                    ))
+                    .cell(),
+                }
+                .cell()
+                .emit();
+                None
+            } else {
+                Some(FollowedEntry::Directory(target, Vc::cell(symlinked_from)))
+            }
+        }
+        SymlinkTarget::None => None,
+    })
+}
+
+fn is_inside_or_equal(path: &str, dir: &str) -> bool {
+    dir.is_empty()
+        || path
+            .strip_prefix(dir)
+            .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Copy)]
+enum SymlinkTarget {
+    File(Vc<FileSystemPath>),
+    Directory(Vc<FileSystemPath>),
+    None,
+}
+
+/// Follows the symlink at `link`, and the symlinks it points to, to a file or
+/// directory. As the target is read through its own path, changes to it
+/// invalidate the caller like changes to `link` do.
+#[turbo_tasks::function]
+async fn resolve_symlink(link: Vc<FileSystemPath>) -> Result<Vc<SymlinkTarget>> {
+    let mut path = link;
+    let mut visited = HashSet::new();
+    loop {
+        match *path.get_type().await? {
+            FileSystemEntryType::File => return Ok(SymlinkTarget::File(path).cell()),
+            FileSystemEntryType::Directory => return Ok(SymlinkTarget::Directory(path).cell()),
+            FileSystemEntryType::Symlink => {}
+            // Dangling symlinks are ignored like missing files.
+            _ => return Ok(SymlinkTarget::None.cell()),
+        }
+
+        if !visited.insert(path.await?.path.clone()) {
+            SymlinkIssue {
+                path: link,
+                message: StyledString::Text(format!(
+                    "The symlink can't be resolved, as {} points back to it.",
+                    path.to_string().await?
+                ))
+                .cell(),
+            }
+            .cell()
+            .emit();
+            return Ok(SymlinkTarget::None.cell());
+        }
+
+        let target = match &*path.read_link().await? {
+            LinkContent::Link { target, link_type } => {
+                let base = if link_type.contains(LinkType::ABSOLUTE) {
+                    path.root()
+                } else {
+                    path.parent()
+                };
+                *base.try_join(target.clone()).await?
+            }
+            // Links to absolute paths outside of the root are invalid.
+            LinkContent::Invalid => None,
+            LinkContent::NotFound => return Ok(SymlinkTarget::None.cell()),
+        };
+        let Some(target) = target else {
+            SymlinkIssue {
+                path: link,
+                message: StyledString::Text(format!(
+                    "{} points outside of the project root, and is not followed.",
+                    path.to_string().await?
+                ))
+                .cell(),
+            }
+            .cell()
+            .emit();
+            return Ok(SymlinkTarget::None.cell());
+        };
+        path = target;
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct SymlinkIssue {
+    path: Vc<FileSystemPath>,
+    message: Vc<StyledString>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for SymlinkIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Unable to follow symlink".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("next app".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(self.message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_inside_or_equal;
+
+    #[test]
+    fn inside_or_equal() {
+        assert!(is_inside_or_equal("app/blog", "app"));
+        assert!(is_inside_or_equal("app", "app"));
+        assert!(is_inside_or_equal("app", ""));
+        assert!(!is_inside_or_equal("apps/blog", "app"));
+        assert!(!is_inside_or_equal("app", "app/blog"));
+    }
+}
@@ -20,11 +20,14 @@ use turbopack_binding::swc::{
     custom_transform::modularize_imports,
 };
 
-use crate::transforms::{
-    cjs_finder::contains_cjs,
-    dynamic::{next_dynamic, NextDynamicMode},
-    fonts::next_font_loaders,
-    react_server_components,
+use crate::{
+    diagnostics::{self, with_error_code},
+    transforms::{
+        cjs_finder::contains_cjs,
+        dynamic::{next_dynamic, NextDynamicMode},
+        fonts::next_font_loaders,
+        react_server_components,
+    },
 };
 
 #[derive(Clone, Debug, Deserialize)]
@@ -54,6 +57,11 @@ pub struct TransformOptions {
     #[serde(default)]
     pub is_server_compiler: bool,
 
+    /// Report diagnostics as a structured list instead of rendering them for a
+    /// terminal.
+    #[serde(default)]
+    pub json_diagnostics: bool,
+
     #[serde(default)]
     pub prefer_esm: bool,
 
@@ -174,18 +182,24 @@ where
     };
 
     chain!(
-        crate::transforms::disallow_re_export_all_in_page::disallow_re_export_all_in_page(opts.is_page_file),
-        match &opts.server_components {
-            Some(config) if config.truthy() =>
-                Either::Left(react_server_components::server_components(
-                    file.name.clone(),
-                    config.clone(),
-                    comments.clone(),
-                    opts.app_dir.clone(),
-                )),
-            _ => Either::Right(noop()),
-        },
-        styled_jsx,
+        with_error_code(
+            diagnostics::DISALLOW_RE_EXPORT_ALL_IN_PAGE,
+            crate::transforms::disallow_re_export_all_in_page::disallow_re_export_all_in_page(opts.is_page_file),
+        ),
+        with_error_code(
+            diagnostics::REACT_SERVER_COMPONENTS,
+            match &opts.server_components {
+                Some(config) if config.truthy() =>
+                    Either::Left(react_server_components::server_components(
+                        file.name.clone(),
+                        config.clone(),
+                        comments.clone(),
+                        opts.app_dir.clone(),
+                    )),
+                _ => Either::Right(noop()),
+            },
+        ),
+        with_error_code(diagnostics::STYLED_JSX, styled_jsx),
         match &opts.styled_components {
             Some(config) => Either::Left(
                 turbopack_binding::swc::custom_transform::styled_components::styled_components(
@@ -197,41 +211,53 @@ where
             ),
             None => Either::Right(noop()),
         },
-        Optional::new(
-            crate::transforms::next_ssg::next_ssg(eliminated_packages),
-            !opts.disable_next_ssg
+        with_error_code(
+            diagnostics::NEXT_SSG,
+            Optional::new(
+                crate::transforms::next_ssg::next_ssg(eliminated_packages),
+                !opts.disable_next_ssg
+            ),
         ),
         crate::transforms::amp_attributes::amp_attributes(),
-        next_dynamic(
-            opts.is_development,
-            opts.is_server_compiler,
-            match &opts.server_components {
-                Some(config) if config.truthy() => match config {
-                    // Always enable the Server Components mode for both
-                    // server and client layers.
-                    react_server_components::Config::WithOptions(config) => config.is_react_server_layer,
+        with_error_code(
+            diagnostics::NEXT_DYNAMIC,
+            next_dynamic(
+                opts.is_development,
+                opts.is_server_compiler,
+                match &opts.server_components {
+                    Some(config) if config.truthy() => match config {
+                        // Always enable the Server Components mode for both
+                        // server and client layers.
+                        react_server_components::Config::WithOptions(config) => config.is_react_server_layer,
+                        _ => false,
+                    },
                     _ => false,
                 },
-                _ => false,
-            },
-            opts.prefer_esm,
-            NextDynamicMode::Webpack,
-            file.name.clone(),
-            opts.pages_dir.clone()
+                opts.prefer_esm,
+                NextDynamicMode::Webpack,
+                file.name.clone(),
+                opts.pages_dir.clone()
+            ),
         ),
-        Optional::new(
-            crate::transforms::page_config::page_config(opts.is_development, opts.is_page_file),
-            !opts.disable_page_config
+        with_error_code(
+            diagnostics::PAGE_CONFIG,
+            Optional::new(
+                crate::transforms::page_config::page_config(opts.is_development, opts.is_page_file),
+                !opts.disable_page_config
+            ),
+        ),
+        with_error_code(diagnostics::RELAY, relay_plugin),
+        with_error_code(
+            diagnostics::REMOVE_CONSOLE,
+            match &opts.remove_console {
+                Some(config) if config.truthy() =>
+                    Either::Left(remove_console::remove_console(
+                        config.clone(),
+                        SyntaxContext::empty().apply_mark(unresolved_mark)
+                    )),
+                _ => Either::Right(noop()),
+            },
         ),
-        relay_plugin,
-        match &opts.remove_console {
-            Some(config) if config.truthy() =>
-                Either::Left(remove_console::remove_console(
-                    config.clone(),
-                    SyntaxContext::empty().apply_mark(unresolved_mark)
-                )),
-            _ => Either::Right(noop()),
-        },
         match &opts.react_remove_properties {
             Some(config) if config.truthy() =>
                 Either::Left(react_remove_properties::react_remove_properties(config.clone())),
@@ -279,25 +305,34 @@ where
         modularize_imports::modularize_imports(
             modularize_imports_config
         ),
-        match &opts.font_loaders {
-            Some(config) => Either::Left(next_font_loaders(config.clone())),
-            None => Either::Right(noop()),
-        },
-        match &opts.server_actions {
-            Some(config) => Either::Left(crate::transforms::server_actions::server_actions(
-                &file.name,
-                config.clone(),
-                comments.clone(),
-            )),
-            None => Either::Right(noop()),
-        },
+        with_error_code(
+            diagnostics::NEXT_FONT_LOADERS,
+            match &opts.font_loaders {
+                Some(config) => Either::Left(next_font_loaders(config.clone())),
+                None => Either::Right(noop()),
+            },
+        ),
+        with_error_code(
+            diagnostics::SERVER_ACTIONS,
+            match &opts.server_actions {
+                Some(config) => Either::Left(crate::transforms::server_actions::server_actions(
+                    &file.name,
+                    config.clone(),
+                    comments.clone(),
+                )),
+                None => Either::Right(noop()),
+            },
+        ),
         match &opts.cjs_require_optimizer {
             Some(config) => {
                 Either::Left(as_folder(crate::transforms::cjs_optimizer::cjs_optimizer(config.clone(), SyntaxContext::empty().apply_mark(unresolved_mark))))
             },
             None => Either::Right(noop()),
         },
-        as_folder(crate::transforms::pure::pure_magic(comments)),
+        with_error_code(
+            diagnostics::PURE,
+            as_folder(crate::transforms::pure::pure_magic(comments)),
+        ),
     )
 }
 
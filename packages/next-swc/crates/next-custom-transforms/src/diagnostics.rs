@@ -0,0 +1,77 @@
+//! Attributes the diagnostics emitted by the custom transforms to the
+//! transform that emitted them.
+//!
+//! The transforms report errors through `HANDLER` without an error code, as
+//! the rendered output is part of their fixtures. Instead, every pass in
+//! [crate::chain_transforms::custom_before_pass] is wrapped with
+//! [with_error_code], and emitters that report structured diagnostics read the
+//! code of the running transform with [current_error_code].
+
+use std::cell::Cell;
+
+use turbopack_binding::swc::core::ecma::{
+    ast::{Module, Program, Script},
+    visit::Fold,
+};
+
+thread_local! {
+    static CURRENT_ERROR_CODE: Cell<Option<&'static str>> = Cell::new(None);
+}
+
+pub const DISALLOW_RE_EXPORT_ALL_IN_PAGE: &str = "next-disallow-re-export-all-in-page";
+pub const REACT_SERVER_COMPONENTS: &str = "next-react-server-components";
+pub const STYLED_JSX: &str = "next-styled-jsx";
+pub const NEXT_SSG: &str = "next-ssg";
+pub const NEXT_DYNAMIC: &str = "next-dynamic";
+pub const PAGE_CONFIG: &str = "next-page-config";
+pub const RELAY: &str = "next-relay";
+pub const REMOVE_CONSOLE: &str = "next-remove-console";
+pub const NEXT_FONT_LOADERS: &str = "next-font-loaders";
+pub const SERVER_ACTIONS: &str = "next-server-actions";
+pub const PURE: &str = "next-pure";
+
+/// The error code of the transform currently running on this thread, if any.
+pub fn current_error_code() -> Option<&'static str> {
+    CURRENT_ERROR_CODE.with(|code| code.get())
+}
+
+/// Wraps `pass` so that diagnostics emitted while it runs are attributed to
+/// `code`.
+pub fn with_error_code<P: Fold>(code: &'static str, pass: P) -> WithErrorCode<P> {
+    WithErrorCode { code, pass }
+}
+
+pub struct WithErrorCode<P> {
+    code: &'static str,
+    pass: P,
+}
+
+impl<P> WithErrorCode<P> {
+    fn run<T>(&mut self, f: impl FnOnce(&mut P) -> T) -> T {
+        /// Restores the previous code even if the pass panics.
+        struct Restore(Option<&'static str>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT_ERROR_CODE.with(|code| code.set(self.0));
+            }
+        }
+
+        let _restore = Restore(CURRENT_ERROR_CODE.with(|code| code.replace(Some(self.code))));
+        f(&mut self.pass)
+    }
+}
+
+impl<P: Fold> Fold for WithErrorCode<P> {
+    fn fold_program(&mut self, n: Program) -> Program {
+        self.run(|pass| pass.fold_program(n))
+    }
+
+    fn fold_module(&mut self, n: Module) -> Module {
+        self.run(|pass| pass.fold_module(n))
+    }
+
+    fn fold_script(&mut self, n: Script) -> Script {
+        self.run(|pass| pass.fold_script(n))
+    }
+}
@@ -34,4 +34,6 @@ DEALINGS IN THE SOFTWARE.
 #![feature(arbitrary_self_types)]
 
 pub mod chain_transforms;
+pub mod diagnostics;
+pub mod module_analysis;
 pub mod transforms;
@@ -0,0 +1,367 @@
+//! A read-only analysis of the Next.js specific facts of a module.
+//!
+//! Reuses the collectors of the transforms, so the reported action ids and font
+//! loader calls match what the transforms would act on, but never generates any
+//! code. Misplaced directives and invalid Server Actions aren't reported as
+//! errors.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use turbopack_binding::swc::core::{
+    common::{collections::AHashMap, BytePos, Mark, SourceFile, Span},
+    ecma::{
+        ast::*,
+        atoms::JsWord,
+        parser::Syntax,
+        transforms::base::resolver,
+        visit::{noop_visit_type, Visit, VisitMutWith, VisitWith},
+    },
+};
+
+use crate::transforms::{
+    fonts::collect_font_functions,
+    import_analyzer::ImportMap,
+    server_actions::{collect_server_actions, ActionsMap},
+};
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeOptions {
+    #[serde(default)]
+    pub syntax: Syntax,
+    /// The modules whose exports are font loaders.
+    #[serde(default = "default_font_loaders")]
+    pub font_loaders: Vec<JsWord>,
+}
+
+impl Default for AnalyzeOptions {
+    fn default() -> Self {
+        AnalyzeOptions {
+            syntax: Default::default(),
+            font_loaders: default_font_loaders(),
+        }
+    }
+}
+
+fn default_font_loaders() -> Vec<JsWord> {
+    vec![
+        "next/font/google".into(),
+        "next/font/local".into(),
+        "@next/font/google".into(),
+        "@next/font/local".into(),
+    ]
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleAnalysis {
+    /// Whether the module starts with a `"use client"` directive.
+    pub use_client: bool,
+    /// Whether the module starts with a `"use server"` directive.
+    pub use_server: bool,
+    /// The Server Actions of the module, by action id.
+    pub actions: ActionsMap,
+    /// The value of `export const config`, if it can be statically analyzed.
+    pub config: Option<Value>,
+    pub has_get_static_props: bool,
+    pub has_get_server_side_props: bool,
+    pub has_generate_static_params: bool,
+    pub dynamic_imports: Vec<DynamicImport>,
+    pub font_loader_calls: Vec<FontLoaderCall>,
+}
+
+/// A `next/dynamic` call.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicImport {
+    pub span: SourceRange,
+    /// The module imported by the loader, e.g. `./component` for
+    /// `dynamic(() => import('./component'))`.
+    pub imported_module: Option<String>,
+    /// The `ssr` option, if it's a literal.
+    pub ssr: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FontLoaderCall {
+    pub span: SourceRange,
+    pub loader: String,
+    /// The imported font function, `None` for default imports like
+    /// `next/font/local`.
+    pub function_name: Option<String>,
+    /// The arguments of the call, `null` for the ones that can't be statically
+    /// analyzed.
+    pub arguments: Vec<Value>,
+}
+
+/// Byte offsets into the file.
+#[derive(Debug, Serialize)]
+pub struct SourceRange {
+    pub start: u32,
+    /// Exclusive.
+    pub end: u32,
+}
+
+/// Analyzes `module`, which has been parsed from `file`. Must be called within
+/// `GLOBALS`.
+pub fn analyze_module(
+    file: &SourceFile,
+    mut module: Module,
+    options: &AnalyzeOptions,
+) -> ModuleAnalysis {
+    module.visit_mut_with(&mut resolver(
+        Mark::new(),
+        Mark::new(),
+        options.syntax.typescript(),
+    ));
+
+    let directives = top_level_directives(&module);
+    let export_names = export_names(&module);
+    let has_export = |name: &str| export_names.iter().any(|export| export == name);
+
+    let mut analyzer = Analyzer {
+        file_start: file.start_pos,
+        imports: ImportMap::analyze(&module),
+        font_functions: collect_font_functions(&options.font_loaders, &module),
+        dynamic_imports: vec![],
+        font_loader_calls: vec![],
+    };
+    module.visit_with(&mut analyzer);
+
+    ModuleAnalysis {
+        use_client: directives
+            .iter()
+            .any(|directive| *directive == "use client"),
+        use_server: directives
+            .iter()
+            .any(|directive| *directive == "use server"),
+        actions: collect_server_actions(&file.name, &module),
+        config: config_export(&module),
+        has_get_static_props: has_export("getStaticProps"),
+        has_get_server_side_props: has_export("getServerSideProps"),
+        has_generate_static_params: has_export("generateStaticParams"),
+        dynamic_imports: analyzer.dynamic_imports,
+        font_loader_calls: analyzer.font_loader_calls,
+    }
+}
+
+struct Analyzer {
+    file_start: BytePos,
+    imports: ImportMap,
+    font_functions: AHashMap<Id, (JsWord, Option<JsWord>)>,
+    dynamic_imports: Vec<DynamicImport>,
+    font_loader_calls: Vec<FontLoaderCall>,
+}
+
+impl Analyzer {
+    fn range(&self, span: Span) -> SourceRange {
+        SourceRange {
+            start: span.lo.0 - self.file_start.0,
+            end: span.hi.0 - self.file_start.0,
+        }
+    }
+}
+
+impl Visit for Analyzer {
+    noop_visit_type!();
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        call.visit_children_with(self);
+
+        let Callee::Expr(callee) = &call.callee else {
+            return;
+        };
+
+        if self.imports.is_import(callee, "next/dynamic", "default") {
+            let mut finder = ImportFinder::default();
+            if let Some(loader) = call.args.first() {
+                loader.expr.visit_with(&mut finder);
+            }
+            let ssr = call
+                .args
+                .get(1)
+                .and_then(|options| object_prop(&options.expr, "ssr"))
+                .and_then(|ssr| match ssr {
+                    Expr::Lit(Lit::Bool(ssr)) => Some(ssr.value),
+                    _ => None,
+                });
+            self.dynamic_imports.push(DynamicImport {
+                span: self.range(call.span),
+                imported_module: finder.imported_module,
+                ssr,
+            });
+        } else if let Expr::Ident(ident) = &**callee {
+            let Some((loader, function_name)) = self.font_functions.get(&ident.to_id()) else {
+                return;
+            };
+            let arguments = call
+                .args
+                .iter()
+                .map(|arg| match arg.spread {
+                    Some(_) => Value::Null,
+                    None => static_value(&arg.expr).unwrap_or(Value::Null),
+                })
+                .collect();
+            self.font_loader_calls.push(FontLoaderCall {
+                span: self.range(call.span),
+                loader: loader.to_string(),
+                function_name: function_name.as_ref().map(|name| name.to_string()),
+                arguments,
+            });
+        }
+    }
+}
+
+/// Finds the first `import('...')` with a string literal specifier.
+#[derive(Default)]
+struct ImportFinder {
+    imported_module: Option<String>,
+}
+
+impl Visit for ImportFinder {
+    noop_visit_type!();
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if self.imported_module.is_some() {
+            return;
+        }
+        if let Callee::Import(_) = call.callee {
+            if let Some(Expr::Lit(Lit::Str(specifier))) = call.args.first().map(|arg| &*arg.expr) {
+                self.imported_module = Some(specifier.value.to_string());
+                return;
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
+/// The string literal statements at the top of the module.
+fn top_level_directives(module: &Module) -> Vec<&JsWord> {
+    module
+        .body
+        .iter()
+        .map_while(|item| match item {
+            ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                expr: box Expr::Lit(Lit::Str(str)),
+                ..
+            })) => Some(&str.value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The names of the declarations and local bindings the module exports.
+fn export_names(module: &Module) -> Vec<JsWord> {
+    let mut names = vec![];
+    for decl in module.body.iter().filter_map(ModuleItem::as_module_decl) {
+        match decl {
+            ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => match decl {
+                Decl::Class(class) => names.push(class.ident.sym.clone()),
+                Decl::Fn(f) => names.push(f.ident.sym.clone()),
+                Decl::Var(var) => names.extend(
+                    var.decls
+                        .iter()
+                        .filter_map(|decl| Some(decl.name.as_ident()?.id.sym.clone())),
+                ),
+                _ => {}
+            },
+            ModuleDecl::ExportNamed(NamedExport { specifiers, .. }) => {
+                names.extend(specifiers.iter().filter_map(|specifier| {
+                    let ExportSpecifier::Named(named) = specifier else {
+                        return None;
+                    };
+                    Some(match named.exported.as_ref().unwrap_or(&named.orig) {
+                        ModuleExportName::Ident(ident) => ident.sym.clone(),
+                        ModuleExportName::Str(str) => str.value.clone(),
+                    })
+                }))
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn config_export(module: &Module) -> Option<Value> {
+    module
+        .body
+        .iter()
+        .filter_map(|item| item.as_module_decl()?.as_export_decl()?.decl.as_var())
+        .flat_map(|var| var.decls.iter())
+        .find(|decl| {
+            decl.name
+                .as_ident()
+                .map_or(false, |ident| &*ident.id.sym == "config")
+        })?
+        .init
+        .as_deref()
+        .and_then(static_value)
+}
+
+/// The value of the property `key` of an object literal.
+fn object_prop<'a>(expr: &'a Expr, key: &str) -> Option<&'a Expr> {
+    let Expr::Object(object) = expr else {
+        return None;
+    };
+    object.props.iter().find_map(|prop| {
+        let PropOrSpread::Prop(box Prop::KeyValue(prop)) = prop else {
+            return None;
+        };
+        (prop_name(&prop.key)? == key).then_some(&*prop.value)
+    })
+}
+
+fn prop_name(key: &PropName) -> Option<String> {
+    match key {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(str) => Some(str.value.to_string()),
+        _ => None,
+    }
+}
+
+/// Converts an expression made only of literals to JSON, or `None` if it
+/// depends on anything else.
+fn static_value(expr: &Expr) -> Option<Value> {
+    match expr {
+        Expr::Lit(Lit::Str(str)) => Some(Value::String(str.value.to_string())),
+        Expr::Lit(Lit::Bool(bool)) => Some(Value::Bool(bool.value)),
+        Expr::Lit(Lit::Null(_)) => Some(Value::Null),
+        Expr::Lit(Lit::Num(num)) => Number::from_f64(num.value).map(Value::Number),
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg: box Expr::Lit(Lit::Num(num)),
+            ..
+        }) => Number::from_f64(-num.value).map(Value::Number),
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl
+            .quasis
+            .first()
+            .and_then(|quasi| quasi.cooked.as_ref())
+            .map(|cooked| Value::String(cooked.to_string())),
+        Expr::Array(array) => array
+            .elems
+            .iter()
+            .map(|elem| match elem {
+                Some(ExprOrSpread { spread: None, expr }) => static_value(expr),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+        Expr::Object(object) => object
+            .props
+            .iter()
+            .map(|prop| {
+                let PropOrSpread::Prop(box Prop::KeyValue(prop)) = prop else {
+                    return None;
+                };
+                Some((prop_name(&prop.key)?, static_value(&prop.value)?))
+            })
+            .collect::<Option<Map<_, _>>>()
+            .map(Value::Object),
+        Expr::Paren(ParenExpr { expr, .. })
+        | Expr::TsAs(TsAsExpr { expr, .. })
+        | Expr::TsConstAssertion(TsConstAssertion { expr, .. })
+        | Expr::TsSatisfies(TsSatisfiesExpr { expr, .. }) => static_value(expr),
+        _ => None,
+    }
+}
@@ -3,7 +3,7 @@ use serde::Deserialize;
 use turbopack_binding::swc::core::{
     common::{collections::AHashMap, BytePos, Spanned},
     ecma::{
-        ast::{Id, ModuleItem},
+        ast::{Id, Module, ModuleItem},
         atoms::JsWord,
         visit::{as_folder, noop_visit_mut_type, Fold, VisitMut, VisitWith},
     },
@@ -29,6 +29,24 @@ pub fn next_font_loaders(config: Config) -> impl Fold + VisitMut {
     })
 }
 
+/// Finds the functions imported from `font_loaders`, returning the loader and
+/// the imported name (`None` for default imports) of each local binding.
+pub(crate) fn collect_font_functions(
+    font_loaders: &[JsWord],
+    module: &Module,
+) -> AHashMap<Id, (JsWord, Option<JsWord>)> {
+    let mut state = State::default();
+    module.visit_with(&mut font_functions_collector::FontFunctionsCollector {
+        font_loaders,
+        state: &mut state,
+    });
+    state
+        .font_functions
+        .into_iter()
+        .map(|(id, function)| (id, (function.loader, function.function_name)))
+        .collect()
+}
+
 #[derive(Debug)]
 pub struct FontFunction {
     loader: JsWord,
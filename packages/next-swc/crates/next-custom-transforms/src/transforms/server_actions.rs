@@ -17,7 +17,10 @@ use turbopack_binding::swc::core::{
         ast::*,
         atoms::JsWord,
         utils::{private_ident, quote_ident, ExprFactory},
-        visit::{as_folder, noop_visit_mut_type, Fold, VisitMut, VisitMutWith},
+        visit::{
+            as_folder, noop_visit_mut_type, noop_visit_type, Fold, Visit, VisitMut, VisitMutWith,
+            VisitWith,
+        },
     },
 };
 
@@ -68,6 +71,29 @@ pub fn server_actions<C: Comments>(
     })
 }
 
+/// Collects the Server Actions of a module as [server_actions] would annotate
+/// them, without validating or changing the module.
+pub fn collect_server_actions(file_name: &FileName, module: &Module) -> ActionsMap {
+    let mut collector = ActionCollector {
+        in_action_file: false,
+        in_export_decl: false,
+        action_cnt: 0,
+        exported_names: vec![],
+        export_actions: vec![],
+    };
+    module.visit_with(&mut collector);
+
+    let mut actions = collector.export_actions;
+    if collector.in_action_file {
+        actions.extend(collector.exported_names);
+    }
+
+    actions
+        .into_iter()
+        .map(|name| (generate_action_id(&file_name.to_string(), &name), name))
+        .collect()
+}
+
 /// Serializes the Server Actions into a magic comment prefixed by
 /// `__next_internal_action_entry_do_not_use__`.
 fn generate_server_actions_comment(actions: ActionsMap) -> String {
@@ -1125,6 +1151,198 @@ impl<C: Comments> VisitMut for ServerActions<C> {
     noop_visit_mut_type!();
 }
 
+/// Names the actions of a module like [ServerActions] does, without
+/// validating or changing it.
+struct ActionCollector {
+    in_action_file: bool,
+    in_export_decl: bool,
+    action_cnt: u32,
+    /// The exports of a `"use server"` file.
+    exported_names: Vec<String>,
+    /// The hoisted inline actions.
+    export_actions: Vec<String>,
+}
+
+impl ActionCollector {
+    fn is_action(&self, body: Option<&BlockStmt>) -> bool {
+        (self.in_action_file && self.in_export_decl)
+            || body.map_or(false, |body| has_server_directive(&body.stmts))
+    }
+
+    fn visit_fn_children<N: VisitWith<Self>>(&mut self, n: &N) {
+        let old_in_export_decl = self.in_export_decl;
+        self.in_export_decl = false;
+        n.visit_children_with(self);
+        self.in_export_decl = old_in_export_decl;
+    }
+
+    fn hoist(&mut self) {
+        let action_name = gen_ident(&mut self.action_cnt);
+        self.export_actions.push(action_name.to_string());
+    }
+
+    fn collect_export(&mut self, item: &ModuleItem) {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => match decl {
+                Decl::Fn(f) => self.exported_names.push(f.ident.sym.to_string()),
+                Decl::Var(var) => self.exported_names.extend(
+                    collect_idents_in_var_decls(&var.decls)
+                        .into_iter()
+                        .map(|id| id.0.to_string()),
+                ),
+                _ => {}
+            },
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+                src: None,
+                specifiers,
+                ..
+            })) => {
+                for spec in specifiers {
+                    if let ExportSpecifier::Named(ExportNamedSpecifier {
+                        orig: ModuleExportName::Ident(ident),
+                        exported,
+                        ..
+                    }) = spec
+                    {
+                        self.exported_names.push(match exported {
+                            Some(ModuleExportName::Ident(exported)) => exported.sym.to_string(),
+                            Some(ModuleExportName::Str(exported)) => exported.value.to_string(),
+                            None => ident.sym.to_string(),
+                        });
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                decl: DefaultDecl::Fn(f),
+                ..
+            })) => {
+                if f.ident.is_none() {
+                    // The transform names anonymous default exports.
+                    gen_ident(&mut self.action_cnt);
+                }
+                self.exported_names.push("default".into());
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(default_expr)) => {
+                match &*default_expr.expr {
+                    Expr::Arrow(ArrowExpr { is_async: true, .. }) | Expr::Call(_) => {
+                        gen_ident(&mut self.action_cnt);
+                        self.exported_names.push("default".into());
+                    }
+                    Expr::Ident(_) => self.exported_names.push("default".into()),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visit for ActionCollector {
+    noop_visit_type!();
+
+    fn visit_module_items(&mut self, items: &[ModuleItem]) {
+        self.in_action_file = items
+            .iter()
+            .take_while(|item| {
+                matches!(
+                    item,
+                    ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                        expr: box Expr::Lit(Lit::Str(_))
+                            | box Expr::Paren(ParenExpr {
+                                expr: box Expr::Lit(Lit::Str(_)),
+                                ..
+                            }),
+                        ..
+                    }))
+                )
+            })
+            .any(|item| {
+                matches!(
+                    item,
+                    ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                        expr: box Expr::Lit(Lit::Str(Str { value, .. })),
+                        ..
+                    })) if value == "use server"
+                )
+            });
+
+        for item in items {
+            if self.in_action_file {
+                self.collect_export(item);
+            }
+            item.visit_with(self);
+        }
+    }
+
+    fn visit_export_decl(&mut self, decl: &ExportDecl) {
+        let old = self.in_export_decl;
+        self.in_export_decl = true;
+        decl.decl.visit_with(self);
+        self.in_export_decl = old;
+    }
+
+    fn visit_export_default_decl(&mut self, decl: &ExportDefaultDecl) {
+        let old = self.in_export_decl;
+        self.in_export_decl = true;
+        decl.decl.visit_with(self);
+        self.in_export_decl = old;
+    }
+
+    fn visit_export_default_expr(&mut self, expr: &ExportDefaultExpr) {
+        let old = self.in_export_decl;
+        self.in_export_decl = true;
+        expr.expr.visit_with(self);
+        self.in_export_decl = old;
+    }
+
+    fn visit_fn_expr(&mut self, f: &FnExpr) {
+        let is_action_fn = self.is_action(f.function.body.as_ref());
+        self.visit_fn_children(f);
+
+        if is_action_fn && !self.in_action_file {
+            if f.ident.is_none() {
+                gen_ident(&mut self.action_cnt);
+            }
+            self.hoist();
+        }
+    }
+
+    fn visit_fn_decl(&mut self, f: &FnDecl) {
+        let is_action_fn = self.is_action(f.function.body.as_ref());
+        self.visit_fn_children(f);
+
+        if is_action_fn && !self.in_action_file {
+            self.hoist();
+        }
+    }
+
+    fn visit_arrow_expr(&mut self, a: &ArrowExpr) {
+        let is_action_fn = self.is_action(match &*a.body {
+            BlockStmtOrExpr::BlockStmt(block) => Some(block),
+            _ => None,
+        });
+        self.visit_fn_children(a);
+
+        if is_action_fn {
+            self.hoist();
+        }
+    }
+}
+
+/// Whether the leading directives of a function body include `"use server"`.
+fn has_server_directive(stmts: &[Stmt]) -> bool {
+    stmts
+        .iter()
+        .map_while(|stmt| match stmt {
+            Stmt::Expr(ExprStmt {
+                expr: box Expr::Lit(Lit::Str(Str { value, .. })),
+                ..
+            }) => Some(value),
+            _ => None,
+        })
+        .any(|value| value == "use server")
+}
+
 fn retain_names_from_declared_idents(child_names: &mut Vec<Name>, current_declared_idents: &[Id]) {
     // Collect all the identifiers defined inside the closure and used
     // in the action function. With deduplication.
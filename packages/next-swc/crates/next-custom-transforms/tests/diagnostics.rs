@@ -0,0 +1,95 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use next_custom_transforms::{
+    diagnostics::{self, current_error_code, with_error_code},
+    transforms::server_actions::{self, server_actions},
+};
+use turbopack_binding::swc::core::{
+    common::{
+        chain,
+        comments::NoopComments,
+        errors::{DiagnosticBuilder, Emitter, Handler, Level, HANDLER},
+        FileName, FilePathMapping, Mark, SourceMap, GLOBALS,
+    },
+    ecma::{
+        ast::{EsVersion, Program},
+        parser::parse_file_as_module,
+        transforms::base::resolver,
+        visit::FoldWith,
+    },
+};
+
+/// `update` is a Server Action, but isn't async.
+const SOURCE: &str = r#"export default function Page() {
+  function update() {
+    'use server'
+  }
+  return null
+}
+"#;
+
+/// Records the level, message, and the error code of the running transform of
+/// every diagnostic.
+struct RecordingEmitter(Arc<Mutex<Vec<(Level, String, Option<&'static str>)>>>);
+
+impl Emitter for RecordingEmitter {
+    fn emit(&mut self, db: &DiagnosticBuilder<'_>) {
+        self.0
+            .lock()
+            .unwrap()
+            .push((db.level, db.message(), current_error_code()));
+    }
+}
+
+#[test]
+fn attributes_diagnostics_to_the_running_transform() {
+    let recorded = Arc::new(Mutex::new(vec![]));
+    let handler = Handler::with_emitter(true, false, Box::new(RecordingEmitter(recorded.clone())));
+
+    GLOBALS.set(&Default::default(), || {
+        HANDLER.set(&handler, || {
+            let cm = SourceMap::new(FilePathMapping::empty());
+            let fm = cm.new_source_file(
+                FileName::Real(PathBuf::from("/app/page.js")),
+                SOURCE.to_string(),
+            );
+            let module = parse_file_as_module(
+                &fm,
+                Default::default(),
+                EsVersion::latest(),
+                None,
+                &mut vec![],
+            )
+            .unwrap();
+
+            assert_eq!(current_error_code(), None);
+            Program::Module(module).fold_with(&mut chain!(
+                resolver(Mark::new(), Mark::new(), false),
+                with_error_code(
+                    diagnostics::SERVER_ACTIONS,
+                    server_actions(
+                        &fm.name,
+                        server_actions::Config {
+                            is_react_server_layer: true,
+                            enabled: true,
+                        },
+                        NoopComments,
+                    ),
+                ),
+            ));
+            assert_eq!(current_error_code(), None);
+        })
+    });
+
+    assert_eq!(
+        *recorded.lock().unwrap(),
+        [(
+            Level::Error,
+            "Server actions must be async functions".to_string(),
+            Some(diagnostics::SERVER_ACTIONS),
+        )]
+    );
+}
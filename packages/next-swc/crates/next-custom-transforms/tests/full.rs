@@ -66,6 +66,7 @@ fn test(input: &Path, minify: bool) {
                 is_page_file: false,
                 is_development: true,
                 is_server_compiler: false,
+                json_diagnostics: false,
                 server_components: None,
                 styled_components: Some(assert_json("{}")),
                 styled_jsx: assert_json("{}"),
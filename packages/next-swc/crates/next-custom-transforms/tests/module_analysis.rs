@@ -0,0 +1,195 @@
+use next_custom_transforms::module_analysis::{analyze_module, AnalyzeOptions, ModuleAnalysis};
+use serde_json::json;
+use turbopack_binding::swc::core::{
+    common::{FileName, FilePathMapping, SourceMap, GLOBALS},
+    ecma::{ast::EsVersion, parser::parse_file_as_module},
+};
+
+fn analyze(filename: &str, src: &str) -> ModuleAnalysis {
+    GLOBALS.set(&Default::default(), || {
+        let cm = SourceMap::new(FilePathMapping::empty());
+        let fm = cm.new_source_file(FileName::Real(filename.into()), src.to_owned());
+        let options = AnalyzeOptions::default();
+        let module =
+            parse_file_as_module(&fm, options.syntax, EsVersion::latest(), None, &mut vec![])
+                .unwrap();
+        analyze_module(&fm, module, &options)
+    })
+}
+
+#[test]
+fn should_report_directives() {
+    let analysis = analyze(
+        "/app/button.js",
+        "'use client'\nexport default function Button() {}",
+    );
+    assert!(analysis.use_client);
+    assert!(!analysis.use_server);
+    assert!(analysis.actions.is_empty());
+
+    // Not at the top of the file, so not a directive.
+    let analysis = analyze(
+        "/app/button.js",
+        "import React from 'react'\n'use client'\nexport default function Button() {}",
+    );
+    assert!(!analysis.use_client);
+}
+
+#[test]
+fn should_collect_action_file_exports() {
+    let analysis = analyze(
+        "/app/actions.js",
+        r#"'use server'
+
+export const foo = async () => {}
+
+const bar = async () => {}
+export { bar }
+
+export default async (a, b) => {}
+"#,
+    );
+    assert!(analysis.use_server);
+    assert_eq!(
+        serde_json::to_value(&analysis.actions).unwrap(),
+        json!({
+            "f2b773827a0a2dfd992ee9aaaa7cdd96924bbe9f": "$$ACTION_0",
+            "4a57ad78d1775984c4e13bb3157a7067bf332926": "$$ACTION_2",
+            "7435b53c9e3d2d68e9825ad2264aa8356475d522": "foo",
+            "9dc9192dfb174e2bb0d06b83023b1b57d891a1a9": "bar",
+            "43064b6982129fc89d7583446909a632963b7383": "default",
+        })
+    );
+}
+
+#[test]
+fn should_collect_inline_actions_without_diagnostics() {
+    // `update` isn't async, which the transform reports as an error. The
+    // analysis must not touch the diagnostics handler, which isn't set here.
+    let analysis = analyze(
+        "/app/page.js",
+        r#"export default function Page() {
+  async function create() {
+    'use server'
+  }
+  function update() {
+    'use server'
+  }
+  const remove = async function () {
+    'use server'
+  }
+  return null
+}
+"#,
+    );
+    assert!(!analysis.use_server);
+    assert_eq!(
+        serde_json::to_value(&analysis.actions).unwrap(),
+        json!({
+            "297fcc7c39709a893c9c505d9c2698587d0fb013": "$$ACTION_0",
+            "eb233c30c017dd3180848355b91cbafc829b5c42": "$$ACTION_1",
+            "265ac47354e3d234c58b0b1fbeeea18675f743fe": "$$ACTION_3",
+        })
+    );
+}
+
+#[test]
+fn should_report_config_and_data_fetching_exports() {
+    let analysis = analyze(
+        "/pages/index.js",
+        r#"export const config = {
+  runtime: 'edge',
+  regions: ['iad1', `sfo1`],
+  maxDuration: -5,
+  unstable_allowDynamic: null,
+}
+
+export async function getStaticProps() {}
+export const getServerSideProps = async () => {}
+function generateStaticParams() {}
+export { generateStaticParams }
+"#,
+    );
+    assert_eq!(
+        analysis.config,
+        Some(json!({
+            "runtime": "edge",
+            "regions": ["iad1", "sfo1"],
+            "maxDuration": -5.0,
+            "unstable_allowDynamic": null,
+        }))
+    );
+    assert!(analysis.has_get_static_props);
+    assert!(analysis.has_get_server_side_props);
+    assert!(analysis.has_generate_static_params);
+
+    let analysis = analyze(
+        "/pages/index.js",
+        "const runtime = 'edge'\nexport const config = { runtime }",
+    );
+    assert_eq!(analysis.config, None);
+    assert!(!analysis.has_get_static_props);
+    assert!(!analysis.has_get_server_side_props);
+    assert!(!analysis.has_generate_static_params);
+}
+
+#[test]
+fn should_report_dynamic_imports() {
+    let analysis = analyze(
+        "/app/page.js",
+        r#"import dynamic from 'next/dynamic'
+const A = dynamic(() => import('./a'), { ssr: false })
+const B = dynamic(async () => (await import('./b')).B)
+const C = dynamic(() => load(), { ssr: process.env.SSR })
+"#,
+    );
+    assert_eq!(
+        serde_json::to_value(&analysis.dynamic_imports).unwrap(),
+        json!([
+            {
+                "span": { "start": 45, "end": 89 },
+                "importedModule": "./a",
+                "ssr": false,
+            },
+            {
+                "span": { "start": 100, "end": 144 },
+                "importedModule": "./b",
+                "ssr": null,
+            },
+            {
+                "span": { "start": 155, "end": 202 },
+                "importedModule": null,
+                "ssr": null,
+            },
+        ])
+    );
+}
+
+#[test]
+fn should_report_font_loader_calls() {
+    let analysis = analyze(
+        "/app/layout.js",
+        r#"import { Inter as Sans } from 'next/font/google'
+import localFont from 'next/font/local'
+const sans = Sans({ subsets: ['latin'], weight: '400' })
+const local = localFont({ src: './font.woff2' }, options)
+"#,
+    );
+    assert_eq!(
+        serde_json::to_value(&analysis.font_loader_calls).unwrap(),
+        json!([
+            {
+                "span": { "start": 102, "end": 145 },
+                "loader": "next/font/google",
+                "functionName": "Inter",
+                "arguments": [{ "subsets": ["latin"], "weight": "400" }],
+            },
+            {
+                "span": { "start": 160, "end": 203 },
+                "loader": "next/font/local",
+                "functionName": null,
+                "arguments": [{ "src": "./font.woff2" }, null],
+            },
+        ])
+    );
+}
@@ -2,11 +2,14 @@ use std::sync::Arc;
 
 use anyhow::{Context, Error};
 use js_sys::JsString;
-use next_custom_transforms::chain_transforms::{custom_before_pass, TransformOptions};
+use next_custom_transforms::{
+    chain_transforms::{custom_before_pass, TransformOptions},
+    module_analysis::{analyze_module, AnalyzeOptions},
+};
 use swc_core::common::Mark;
 use turbopack_binding::swc::core::{
     base::{
-        config::{JsMinifyOptions, ParseOptions},
+        config::{IsModule, JsMinifyOptions, ParseOptions},
         try_with_handler, Compiler,
     },
     common::{
@@ -14,7 +17,10 @@ use turbopack_binding::swc::core::{
         errors::ColorConfig,
         FileName, FilePathMapping, SourceMap, GLOBALS,
     },
-    ecma::transforms::base::pass::noop,
+    ecma::{
+        ast::{EsVersion, Program},
+        transforms::base::pass::noop,
+    },
 };
 use wasm_bindgen::{prelude::*, JsCast};
 use wasm_bindgen_futures::future_to_promise;
@@ -190,6 +196,62 @@ pub fn parse(s: JsString, opts: JsValue) -> js_sys::Promise {
     future_to_promise(async { parse_sync(s, opts) })
 }
 
+#[wasm_bindgen(js_name = "analyzeModuleSync")]
+pub fn analyze_module_sync(
+    s: JsString,
+    filename: Option<String>,
+    opts: JsValue,
+) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let c = compiler();
+    let opts: AnalyzeOptions = serde_wasm_bindgen::from_value(opts)?;
+
+    let analysis = try_with_handler(
+        c.cm.clone(),
+        turbopack_binding::swc::core::base::HandlerOpts {
+            color: ColorConfig::Never,
+            skip_filename: false,
+        },
+        |handler| {
+            GLOBALS.set(&Default::default(), || {
+                let fm = c.cm.new_source_file(
+                    filename.map_or(FileName::Anon, |filename| FileName::Real(filename.into())),
+                    s.into(),
+                );
+                let program = c
+                    .parse_js(
+                        fm.clone(),
+                        handler,
+                        EsVersion::latest(),
+                        opts.syntax,
+                        IsModule::Bool(true),
+                        None,
+                    )
+                    .context("failed to parse code")?;
+                let Program::Module(module) = program else {
+                    anyhow::bail!("expected a module");
+                };
+                Ok(analyze_module(&fm, module, &opts))
+            })
+        },
+    )
+    .map_err(convert_err)?;
+
+    Ok(serde_wasm_bindgen::to_value(&analysis)?)
+}
+
+#[wasm_bindgen(js_name = "analyzeModule")]
+pub fn analyze_module_async(
+    s: JsString,
+    filename: Option<String>,
+    opts: JsValue,
+) -> js_sys::Promise {
+    // TODO: This'll be properly scheduled once wasm have standard backed thread
+    // support.
+    future_to_promise(async { analyze_module_sync(s, filename, opts) })
+}
+
 /// Get global sourcemap
 fn compiler() -> Arc<Compiler> {
     let cm = Arc::new(SourceMap::new(FilePathMapping::empty()));
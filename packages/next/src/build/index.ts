@@ -1320,6 +1320,7 @@ export default async function build(
           nextConfig: config,
           jsConfig: await getTurbopackJsConfig(dir, config),
           watch: false,
+          dev: false,
           env: process.env as Record<string, string>,
           defineEnv: createDefineEnv({
             isTurbopack: true,
@@ -410,6 +410,12 @@ export interface ProjectOptions {
    * Whether to watch the filesystem for file changes.
    */
   watch: boolean
+
+  /**
+   * Whether the project is built for `next dev` or for `next build`.
+   * Defaults to `true`.
+   */
+  dev?: boolean
 }
 
 type RustifiedEnv = { name: string; value: string }[]
@@ -621,6 +627,10 @@ export interface Project {
   traceSource(
     stackFrame: TurbopackStackFrame
   ): Promise<TurbopackStackFrame | null>
+  traceSourceBatch(
+    stackFrames: TurbopackStackFrame[]
+  ): Promise<(TurbopackStackFrame | null)[]>
+  getSourceMap(filePath: string): Promise<string | null>
   updateInfoSubscribe(
     aggregationMs: number
   ): AsyncIterableIterator<TurbopackResult<UpdateMessage>>
@@ -1006,6 +1016,16 @@ function bindingToApi(binding: any, _wasm: boolean) {
       return binding.projectTraceSource(this._nativeProject, stackFrame)
     }
 
+    traceSourceBatch(
+      stackFrames: TurbopackStackFrame[]
+    ): Promise<(TurbopackStackFrame | null)[]> {
+      return binding.projectTraceSourceBatch(this._nativeProject, stackFrames)
+    }
+
+    getSourceMap(filePath: string): Promise<string | null> {
+      return binding.projectGetSourceMap(this._nativeProject, filePath)
+    }
+
     getSourceForAsset(filePath: string): Promise<string | null> {
       return binding.projectGetSourceForAsset(this._nativeProject, filePath)
     }